@@ -0,0 +1,80 @@
+#ifndef TAK_H
+#define TAK_H
+
+/* Generated by cbindgen from src/ffi.rs; don't edit by hand */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+typedef struct Game Game;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/*
+ A new game on an empty board, or NULL if the size isn't 3 to 8
+ */
+Game *tak_game_new(uintptr_t size);
+
+/*
+ A game starting from a TPS position, or NULL if it doesn't parse
+ */
+Game *tak_game_from_tps(const char *tps);
+
+/*
+ Releases a game. NULL is ignored.
+ */
+void tak_game_free(Game *game);
+
+/*
+ Plays a PTN move for the player to move, returning 0, or -1 if the move
+ is invalid or illegal
+ */
+int tak_game_play(Game *game, const char *turn);
+
+/*
+ The position in TPS
+ */
+char *tak_game_tps(const Game *game);
+
+/*
+ The PTN result, such as `R-0`, or an empty string while the game goes on
+ */
+char *tak_game_result(const Game *game);
+
+/*
+ 1 or 2 for the winner, 0 while the game goes on, or -1 for NULL
+ */
+int tak_game_winner(const Game *game);
+
+/*
+ Every legal move in PTN, separated by spaces, and empty once the game
+ is over
+ */
+char *tak_game_legal_moves(const Game *game);
+
+/*
+ The AI's move in PTN, searching depth plies, or NULL once the game is
+ over
+ */
+char *tak_ai_move(const Game *game, uintptr_t depth);
+
+/*
+ Releases a string returned by this library. NULL is ignored.
+ */
+void tak_string_free(char *s);
+
+/*
+ Why the last failing call on this thread failed, or NULL. The string
+ belongs to the library and lasts until the next failure.
+ */
+const char *tak_last_error(void);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* TAK_H */
@@ -0,0 +1,80 @@
+use std::sync::atomic::AtomicBool;
+use std::time::Duration;
+use std::time::Instant;
+
+use ai::Ai;
+use ai::search::Searcher;
+use ai::search::TranspositionTable;
+use game::Game;
+use turn::Turn;
+
+/// How hard to look when analyzing a position
+#[derive(Clone, Copy, Debug, PartialEq, Eq, RustcDecodable, RustcEncodable)]
+pub struct Limits {
+    pub depth: usize,
+    pub millis: Option<u64>,
+    /// How many of the best moves to report
+    pub lines: usize,
+}
+
+impl Limits {
+    pub fn new(depth: usize, lines: usize) -> Limits {
+        Limits {
+            depth: depth,
+            millis: None,
+            lines: lines,
+        }
+    }
+}
+
+/// One candidate move, scored for the player to move
+#[derive(Clone, Debug, PartialEq, Eq, RustcDecodable, RustcEncodable)]
+pub struct Line {
+    pub turn: Turn,
+    pub score: i64,
+    /// The expected continuation, starting with turn
+    pub pv: Vec<Turn>,
+    pub depth: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, RustcDecodable, RustcEncodable)]
+pub struct Analysis {
+    /// Best first
+    pub lines: Vec<Line>,
+    pub depth: usize,
+    pub nodes: u64,
+}
+
+impl Ai {
+    /// Scores the best few moves in a position, with their expected
+    /// continuations
+    pub fn analyze(&self, game: &Game, limits: Limits) -> Analysis {
+        let table = TranspositionTable::new();
+        let stop = AtomicBool::new(false);
+        let deadline = limits.millis.map(|ms| Instant::now() + Duration::from_millis(ms));
+        let mut searcher = Searcher::new(&table, &stop, 0).with_weights(self.weights)
+                                                           .with_tablebase(self.tablebase())
+                                                           .with_deadline(deadline);
+        let (scores, depth) = searcher.iterate_scored(game, limits.depth);
+
+        let lines = scores.into_iter().take(limits.lines).map(|(turn, score)| {
+            let mut next = game.clone();
+            let mut pv = vec![turn.clone()];
+            if next.play_turn(turn.clone()).is_ok() {
+                pv.extend(table.principal_variation(&next, depth.saturating_sub(1)));
+            }
+            Line {
+                turn: turn,
+                score: score,
+                pv: pv,
+                depth: depth,
+            }
+        }).collect();
+
+        Analysis {
+            lines: lines,
+            depth: depth,
+            nodes: searcher.nodes,
+        }
+    }
+}
@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::Read;
+use std::io::Write;
+use std::path::Path;
+use std::str::FromStr;
+
+use game::Game;
+use ptn::Ptn;
+use turn::Turn;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BookMove {
+    pub turn: Turn,
+    pub weight: u32,
+}
+
+/// Opening book mapping positions (by `Game::position_hash`) to weighted
+/// moves
+///
+/// The file format is one move per line, `<hash in hex> <ptn move> <weight>`.
+/// Blank lines and lines starting with `#` are ignored.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Book {
+    entries: HashMap<u64, Vec<BookMove>>,
+}
+
+impl Book {
+    pub fn new() -> Book {
+        Book { entries: HashMap::new() }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Adds weight to a move, creating the entry if needed
+    pub fn add(&mut self, hash: u64, turn: Turn, weight: u32) {
+        let moves = self.entries.entry(hash).or_insert_with(Vec::new);
+        if let Some(index) = moves.iter().position(|m| m.turn == turn) {
+            moves[index].weight += weight;
+        } else {
+            moves.push(BookMove {
+                turn: turn,
+                weight: weight,
+            });
+        }
+    }
+
+    pub fn lookup(&self, game: &Game) -> Option<&[BookMove]> {
+        self.entries.get(&game.position_hash()).map(|moves| moves.as_slice())
+    }
+
+    /// Picks the heaviest legal book move for the position
+    pub fn choose(&self, game: &Game) -> Option<Turn> {
+        let legal = game.legal_moves();
+        let mut best: Option<&BookMove> = None;
+        for m in self.lookup(game).unwrap_or(&[]) {
+            if !legal.contains(&m.turn) {
+                continue;
+            }
+            if best.map(|b| m.weight > b.weight).unwrap_or(true) {
+                best = Some(m);
+            }
+        }
+        best.map(|m| m.turn.clone())
+    }
+
+    /// Adds the first `plies` moves of a game, one weight per occurrence
+    ///
+    /// Nothing is added unless every one of those moves can be played.
+    pub fn add_game(&mut self, moves: &[Turn], size: usize, plies: usize) -> Result<(), String> {
+        if size < 3 || size > 8 {
+            return Err("Unsupported board size".into());
+        }
+        let mut game = Game::new(size);
+        let mut positions = vec![];
+        for turn in moves.iter().take(plies) {
+            positions.push((game.position_hash(), turn.clone()));
+            try!(game.play_turn(turn.clone()));
+        }
+        for (hash, turn) in positions {
+            self.add(hash, turn, 1);
+        }
+        Ok(())
+    }
+
+    /// Builds a book from every `.ptn` file in a directory
+    ///
+    /// Files that can't be parsed or replayed, or are for an unsupported
+    /// board size, are skipped.
+    pub fn from_ptn_dir<P: AsRef<Path>>(dir: P, plies: usize) -> io::Result<Book> {
+        let mut book = Book::new();
+        for entry in try!(fs::read_dir(dir)) {
+            let path = try!(entry).path();
+            if path.extension().map(|e| e != "ptn").unwrap_or(true) {
+                continue;
+            }
+            let mut text = String::new();
+            try!(try!(File::open(&path)).read_to_string(&mut text));
+            if let Ok(ptn) = text.parse::<Ptn>() {
+                if let Some(size) = ptn.size() {
+                    let _ = book.add_game(&ptn.moves, size, plies);
+                }
+            }
+        }
+        Ok(book)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Book> {
+        let mut text = String::new();
+        try!(try!(File::open(path)).read_to_string(&mut text));
+        text.parse::<Book>().map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut file = try!(File::create(path));
+        write!(file, "{}", self)
+    }
+}
+
+impl FromStr for Book {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut book = Book::new();
+        for (number, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields = line.split_whitespace().collect::<Vec<_>>();
+            let error = || format!("Invalid book entry on line {}", number + 1);
+            if fields.len() != 3 {
+                return Err(error());
+            }
+            let hash = try!(u64::from_str_radix(fields[0], 16).map_err(|_| error()));
+            let turn = try!(fields[1].parse::<Turn>().map_err(|_| error()));
+            let weight = try!(fields[2].parse::<u32>().map_err(|_| error()));
+            book.add(hash, turn, weight);
+        }
+        Ok(book)
+    }
+}
+
+impl fmt::Display for Book {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut hashes = self.entries.keys().collect::<Vec<_>>();
+        hashes.sort();
+        for hash in hashes {
+            for m in self.entries[hash].iter() {
+                try!(write!(f, "{:016x} {} {}\n", hash, m.turn, m.weight));
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,162 @@
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::io::Read;
+use std::io::Write;
+use std::path::Path;
+
+use rustc_serialize::json;
+
+use board::Board;
+use piece::Player;
+use piece::Stone;
+
+/// The raw evaluation features for one player
+#[derive(Clone, Copy, Debug, PartialEq, Eq, RustcDecodable, RustcEncodable)]
+pub struct Terms {
+    /// Flats on top of a stack, which count at the end of the game
+    pub flats: i64,
+    /// Placements still needed for a road, or size + 1 if there's no way
+    pub road_distance: i64,
+    /// Flats and capstones not yet played
+    pub reserves: i64,
+    /// Own pieces under the top of controlled stacks, within carry limit
+    pub stack_control: i64,
+    pub walls: i64,
+    pub capstones: i64,
+}
+
+/// How much each term is worth, per unit
+#[derive(Clone, Copy, Debug, PartialEq, Eq, RustcDecodable, RustcEncodable)]
+pub struct Weights {
+    pub flats: i64,
+    pub road_distance: i64,
+    pub reserves: i64,
+    pub stack_control: i64,
+    pub walls: i64,
+    pub capstones: i64,
+}
+
+impl Default for Weights {
+    fn default() -> Weights {
+        Weights {
+            flats: 100,
+            road_distance: -30,
+            reserves: 0,
+            stack_control: 10,
+            walls: 0,
+            capstones: 0,
+        }
+    }
+}
+
+impl Weights {
+    /// Reads weights written by `save`, as JSON
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Weights> {
+        let mut text = String::new();
+        try!(try!(File::open(path)).read_to_string(&mut text));
+        json::decode(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let text = try!(json::encode(self).map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string())));
+        let mut file = try!(File::create(path));
+        write!(file, "{}\n", text)
+    }
+}
+
+impl Terms {
+    pub fn new<T: Board>(board: &T, player: Player) -> Terms {
+        let size = board.size();
+        let mut terms = Terms {
+            flats: 0,
+            road_distance: board.road_distance(player)
+                                .min()
+                                .map(|d| d as i64)
+                                .unwrap_or(size as i64 + 1),
+            reserves: 0,
+            stack_control: 0,
+            walls: 0,
+            capstones: 0,
+        };
+
+        let count = board.count();
+        let (flats, caps) = match player {
+            Player::One => (count.p1_flat, count.p1_cap),
+            Player::Two => (count.p2_flat, count.p2_cap),
+        };
+        terms.reserves = (count.max_flat + count.max_cap) as i64 - (flats + caps) as i64;
+
+        for square in board.squares() {
+            if square.mover() != Some(player) {
+                continue;
+            }
+            let pieces = square.clone().collect::<Vec<_>>();
+            match pieces.last().map(|piece| piece.stone()) {
+                Some(Stone::Flat) => terms.flats += 1,
+                Some(Stone::Standing) => terms.walls += 1,
+                Some(Stone::Capstone) => terms.capstones += 1,
+                None => {}
+            }
+            terms.stack_control += pieces.iter()
+                                         .rev()
+                                         .skip(1)
+                                         .take(size - 1)
+                                         .filter(|piece| piece.owner() == player)
+                                         .count() as i64;
+        }
+        terms
+    }
+
+    /// Each term multiplied by its weight, in the order they're declared
+    pub fn weighted(&self, weights: &Weights) -> [(&'static str, i64); 6] {
+        [("flats", self.flats * weights.flats),
+         ("road_distance", self.road_distance * weights.road_distance),
+         ("reserves", self.reserves * weights.reserves),
+         ("stack_control", self.stack_control * weights.stack_control),
+         ("walls", self.walls * weights.walls),
+         ("capstones", self.capstones * weights.capstones)]
+    }
+
+    pub fn score(&self, weights: &Weights) -> i64 {
+        self.weighted(weights).iter().fold(0, |sum, &(_, x)| sum + x)
+    }
+}
+
+/// Every evaluation term for both players, to explain a score
+#[derive(Clone, Copy, Debug, PartialEq, Eq, RustcDecodable, RustcEncodable)]
+pub struct Breakdown {
+    pub player_one: Terms,
+    pub player_two: Terms,
+    pub weights: Weights,
+}
+
+impl Breakdown {
+    pub fn new<T: Board>(board: &T, weights: &Weights) -> Breakdown {
+        Breakdown {
+            player_one: Terms::new(board, Player::One),
+            player_two: Terms::new(board, Player::Two),
+            weights: *weights,
+        }
+    }
+
+    /// The evaluation from player one's point of view
+    pub fn score(&self) -> i64 {
+        self.player_one.score(&self.weights) - self.player_two.score(&self.weights)
+    }
+}
+
+impl fmt::Display for Breakdown {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let one = self.player_one.weighted(&self.weights);
+        let two = self.player_two.weighted(&self.weights);
+        try!(write!(f, "{:<15}{:>8}{:>8}{:>8}\n", "term", "P1", "P2", "diff"));
+        for (&(name, a), &(_, b)) in one.iter().zip(two.iter()) {
+            try!(write!(f, "{:<15}{:>8}{:>8}{:>8}\n", name, a, b, a - b));
+        }
+        write!(f, "{:<15}{:>8}{:>8}{:>8}\n", "total",
+               self.player_one.score(&self.weights),
+               self.player_two.score(&self.weights),
+               self.score())
+    }
+}
@@ -0,0 +1,108 @@
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::Read;
+use std::io::Write;
+use std::path::Path;
+
+use ai::tune::result_value;
+use board::Board;
+use game::Game;
+use piece::Player;
+use point::Point;
+use ptn::Ptn;
+use turn::Turn;
+
+pub use board::stack_code;
+
+/// The CSV header for a board size
+///
+/// Columns are the ply, the player to move (1 or 2), one stack per square
+/// from a1 across each row to the last square, the flats and capstones each
+/// player has left, the move played and the result for player one (1 for
+/// a win, 0 for a loss, 0.5 for a draw).
+pub fn header(size: usize) -> String {
+    let mut columns = vec!["ply".to_string(), "to_move".to_string()];
+    for y in 0..size {
+        for x in 0..size {
+            columns.push(Point::new(x, y).to_string());
+        }
+    }
+    for name in ["p1_flats", "p1_caps", "p2_flats", "p2_caps", "move", "result"].iter() {
+        columns.push(name.to_string());
+    }
+    columns.join(",")
+}
+
+/// One CSV row for the position before each move of a game
+pub fn rows(moves: &[Turn], size: usize, result: f64) -> Result<Vec<String>, String> {
+    let mut game = Game::new(size);
+    let mut rows = vec![];
+    for turn in moves {
+        rows.push(row(&game, turn, result));
+        try!(game.play_turn(turn.clone()));
+    }
+    Ok(rows)
+}
+
+fn row(game: &Game, turn: &Turn, result: f64) -> String {
+    let board = game.board();
+    let size = board.size();
+    let to_move = if game.next_player() == Player::One { "1" } else { "2" };
+    let mut columns = vec![game.turn_number().to_string(), to_move.to_string()];
+    for y in 0..size {
+        for x in 0..size {
+            columns.push(stack_code(board.at(&Point::new(x, y)).unwrap()));
+        }
+    }
+    let count = board.count();
+    for left in [count.max_flat - count.p1_flat,
+                 count.max_cap - count.p1_cap,
+                 count.max_flat - count.p2_flat,
+                 count.max_cap - count.p2_cap].iter() {
+        columns.push(left.to_string());
+    }
+    columns.push(turn.to_string());
+    columns.push(result.to_string());
+    columns.join(",")
+}
+
+/// Writes every position from the finished games of one size in a
+/// directory of `.ptn` files, returning how many rows were written
+///
+/// Games of other sizes, unfinished games, and files that can't be parsed
+/// or replayed are skipped.
+pub fn export_ptn_dir<P: AsRef<Path>, W: Write>(dir: P, size: usize, out: &mut W) -> io::Result<usize> {
+    try!(writeln!(out, "{}", header(size)));
+    let mut written = 0;
+    for entry in try!(fs::read_dir(dir)) {
+        let path = try!(entry).path();
+        if path.extension().map(|e| e != "ptn").unwrap_or(true) {
+            continue;
+        }
+        let mut text = String::new();
+        try!(try!(File::open(&path)).read_to_string(&mut text));
+        let ptn = match text.parse::<Ptn>() {
+            Ok(ptn) => ptn,
+            Err(_) => continue,
+        };
+        if ptn.size() != Some(size) {
+            continue;
+        }
+        let game = match ptn.game() {
+            Ok(game) => game,
+            Err(_) => continue,
+        };
+        let result = match ptn.tag("Result").and_then(result_value).or(result_value(game.result())) {
+            Some(result) => result,
+            None => continue,
+        };
+        if let Ok(rows) = rows(&ptn.moves, size, result) {
+            for row in rows.iter() {
+                try!(writeln!(out, "{}", row));
+            }
+            written += rows.len();
+        }
+    }
+    Ok(written)
+}
@@ -1,147 +1,259 @@
-use std::collections::BTreeSet;
-
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::thread;
+use std::time::Duration;
+use std::time::Instant;
 
 use board::Board;
 use board_naive::NaiveBoard;
+use game::Game;
 use turn::Turn;
 use turn::Turn::Place;
-use turn::Turn::Slide;
-use turn::Direction;
-use piece::Piece;
 use piece::Player;
 use piece::Stone;
 use point::Point;
 
+pub mod analysis;
+pub mod book;
+pub mod eval;
+pub mod export;
+pub mod moves;
+pub mod perft;
+pub mod puzzle;
+pub mod rng;
+pub mod search;
+pub mod strategy;
+pub mod tablebase;
+pub mod tournament;
+pub mod tune;
+
+use self::book::Book;
+use self::eval::Breakdown;
+use self::eval::Weights;
+use self::moves::MoveIter;
+use self::moves::Phase;
+use self::moves::PLACEMENTS_FIRST;
+use self::rng::Rng;
+use self::search::Searcher;
+use self::search::TranspositionTable;
+use self::tablebase::Tablebase;
+
+/// Preset playing strengths, from easiest to hardest
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Level {
+    Beginner,
+    Casual,
+    Intermediate,
+    Strong,
+}
+
+#[derive(Clone, Debug)]
 pub struct Ai {
     player: Player,
+    depth: usize,
+    threads: usize,
+    book: Option<Arc<Book>>,
+    tablebase: Option<Arc<Tablebase>>,
+    time_limit: Option<Duration>,
+    margin: i64,
+    seed: u64,
+    weights: Weights,
 }
 
 impl Ai {
     pub fn new(player: Player) -> Ai {
-        Ai { player: player }
+        Ai {
+            player: player,
+            depth: 3,
+            threads: 1,
+            book: None,
+            tablebase: None,
+            time_limit: None,
+            margin: 0,
+            seed: 0,
+            weights: Weights::default(),
+        }
     }
 
-    pub fn next_move<T: Board>(&self, turn: usize, board: &T) -> Turn {
-        if turn < 2 {
-            if board.at(&Point::new(0, 0)).unwrap().count() == 0 {
-                Place { point: Point::new(0, 0), stone: Stone::Flat }
-            } else {
-                Place { point: Point::new(0, board.size() - 1), stone: Stone::Flat }
-            }
-        } else {
-            Place { point: Point::new(0, 0), stone: Stone::Flat }
-        }
+    /// Limits depth, and picks among moves close to the best one
+    ///
+    /// Levels don't set a time limit, so a seed always replays the same
+    /// game.
+    pub fn with_level(mut self, level: Level) -> Ai {
+        let (depth, margin) = match level {
+            Level::Beginner => (1, 150),
+            Level::Casual => (2, 60),
+            Level::Intermediate => (3, 20),
+            Level::Strong => (4, 0),
+        };
+        self.depth = depth;
+        self.margin = margin;
+        self
     }
 
-    pub fn possible_moves<T: Board>(&self, board: &T) -> Vec<Turn> {
-        let mut moves = vec![];
-        for x in 0..board.size() {
-            for y in 0..board.size() {
-                let point = Point::new(x, y);
-                let square = board.at(&point).unwrap();
-                if square.clone().count() == 0 {
-                    self.possible_moves_place(board, point, &mut moves);
-                } else if square.mover() == Some(self.player) {
-                    self.possible_moves_slide(board, point, &mut moves);
-                }
-            }
-        }
-        moves
+    /// Stops deepening the search once the time limit has passed
+    ///
+    /// How deep the search gets then depends on the machine, so seeded
+    /// games only replay exactly without a time limit.
+    pub fn with_time_limit(mut self, limit: Duration) -> Ai {
+        self.time_limit = Some(limit);
+        self
+    }
+
+    /// Plays any move scoring within margin of the best one, chosen at
+    /// random. A margin of zero always plays the best move.
+    pub fn with_margin(mut self, margin: i64) -> Ai {
+        self.margin = margin;
+        self
+    }
+
+    /// Seeds the random choice between close moves
+    ///
+    /// The seed is mixed with the position, so the same seed plays the
+    /// same moves in the same positions, and so reproduces whole games.
+    pub fn with_seed(mut self, seed: u64) -> Ai {
+        self.seed = seed;
+        self
+    }
+
+    /// Sets how many plies deep the search looks
+    pub fn with_depth(mut self, depth: usize) -> Ai {
+        self.depth = depth;
+        self
+    }
+
+    /// Sets how many threads search in parallel
+    ///
+    /// Extra threads run the same iterative deepening search (Lazy SMP) and
+    /// only help by filling the shared transposition table, so the move is
+    /// always taken from the main thread. With one thread the result is
+    /// deterministic.
+    pub fn with_threads(mut self, threads: usize) -> Ai {
+        self.threads = threads;
+        self
+    }
+
+    /// Consults the opening book before searching
+    pub fn with_book(mut self, book: Book) -> Ai {
+        self.book = Some(Arc::new(book));
+        self
+    }
+
+    /// Looks up solved endgame positions during search
+    pub fn with_tablebase(mut self, tablebase: Tablebase) -> Ai {
+        self.tablebase = Some(Arc::new(tablebase));
+        self
     }
 
-    fn possible_moves_place<T: Board>(&self, board: &T, point: Point, moves: &mut Vec<Turn>) {
-        if !board.count().used_up(&Piece::new(Stone::Flat, self.player)) {
-            moves.push(Place { point: point, stone: Stone::Flat });
-            moves.push(Place { point: point, stone: Stone::Standing });
+    /// Evaluates positions with these weights, such as ones from `tak tune`
+    pub fn with_weights(mut self, weights: Weights) -> Ai {
+        self.weights = weights;
+        self
+    }
+
+    /// Every term of the static evaluation of game, for both players
+    pub fn explain(&self, game: &Game) -> Breakdown {
+        Breakdown::new(game.board(), &self.weights)
+    }
+
+    /// Picks the move to play in the given game
+    pub fn best_move(&self, game: &Game) -> Turn {
+        if let Some(turn) = self.book.as_ref().and_then(|book| book.choose(game)) {
+            return turn;
         }
-        if !board.count().used_up(&Piece::new(Stone::Capstone, self.player)) {
-            moves.push(Place { point: point, stone: Stone::Capstone });
+        if game.turn_number() < 2 {
+            return self.next_move(game.turn_number(), game.board());
         }
+        self.search(game)
     }
 
-    fn possible_moves_slide<T: Board>(&self, board: &T, point: Point, moves: &mut Vec<Turn>) {
-        let pile_height = board.at(&point).unwrap().count();
-        for dir in Direction::all() {
-            let mut clear = 0;
-            while let Some(point) = dir.adjust(&point, clear + 1, board.size()) {
-                // Deal with blocking in and capstones flattening
-                if let Some(piece) = board.at(&point).unwrap().last() {
-                    if piece.stone() != Stone::Flat {
-                        break;
-                    }
-                }
-                clear += 1;
-            }
-            if clear > 0 {
-                for d in self.bounded_slide(pile_height, clear) {
-                    // Discard the non-move (drops.contents[0] == height)
-                    if d.contents[0] < pile_height {
-                        let num = pile_height - d.contents[0];
-                        let drops = d.contents.into_iter().skip(1).collect::<Vec<usize>>();
-                        moves.push(Slide {
-                            num_pieces: num,
-                            point: point,
-                            direction: dir,
-                            drops: drops
-                        });
-                    }
-                }
-            }
+    fn tablebase(&self) -> Option<&Tablebase> {
+        self.tablebase.as_ref().map(|t| &**t)
+    }
+
+    fn deadline(&self) -> Option<Instant> {
+        self.time_limit.map(|limit| Instant::now() + limit)
+    }
+
+    /// Runs an alpha-beta search to the configured depth
+    ///
+    /// When picking randomly among close moves, every root move needs an
+    /// exact score, so that search runs on a single thread.
+    pub fn search(&self, game: &Game) -> Turn {
+        if self.margin > 0 {
+            return self.search_randomized(game);
+        }
+        let table = Arc::new(TranspositionTable::new());
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let helpers = (1..self.threads).map(|id| {
+            let table = table.clone();
+            let stop = stop.clone();
+            let game = game.clone();
+            let depth = self.depth + id % 2;
+            let weights = self.weights;
+            let tablebase = self.tablebase.clone();
+            thread::spawn(move || {
+                Searcher::new(&table, &stop, id).with_weights(weights)
+                                                .with_tablebase(tablebase.as_ref().map(|t| &**t))
+                                                .iterate(&game, depth);
+            })
+        }).collect::<Vec<_>>();
+
+        let (best, _) = Searcher::new(&table, &stop, 0).with_weights(self.weights)
+                                                       .with_tablebase(self.tablebase())
+                                                       .with_deadline(self.deadline())
+                                                       .iterate(game, self.depth);
+        stop.store(true, Ordering::SeqCst);
+        for helper in helpers {
+            helper.join().unwrap();
         }
+        best.unwrap_or_else(|| self.next_move(game.turn_number(), game.board()))
     }
 
-    fn bounded_slide(&self, height: usize, clear: usize) -> BTreeSet<Drops> {
-        let mut evolving: BTreeSet<Drops> = BTreeSet::new();
-        let start = Drops { last_index: 0, contents: vec![0; clear + 1] };
-        evolving.insert(start);
+    fn search_randomized(&self, game: &Game) -> Turn {
+        let table = TranspositionTable::new();
+        let stop = AtomicBool::new(false);
+        let (scores, _) = Searcher::new(&table, &stop, 0).with_weights(self.weights)
+                                                        .with_tablebase(self.tablebase())
+                                                        .with_deadline(self.deadline())
+                                                        .iterate_scored(game, self.depth);
+        let best = match scores.first() {
+            Some(&(_, score)) => score,
+            None => return self.next_move(game.turn_number(), game.board()),
+        };
+        let mut close = scores.into_iter()
+                              .filter(|&(_, score)| score >= best - self.margin)
+                              .collect::<Vec<_>>();
+        let mut rng = Rng::new(self.seed ^ game.position_hash());
+        let index = rng.below(close.len());
+        close.swap_remove(index).0
+    }
 
-        println!("{} {}", height, clear);
-        for _ in 0..height {
-            let mut evolving_next = BTreeSet::new();
-            for vec in evolving {
-                evolving_next.insert(add_to_last(&vec));
-                if let Some(v) = add_to_length(&vec) {
-                    evolving_next.insert(v);
-                }
+    pub fn next_move<T: Board>(&self, turn: usize, board: &T) -> Turn {
+        if turn < 2 {
+            if board.at(&Point::new(0, 0)).unwrap().count() == 0 {
+                Place { point: Point::new(0, 0), stone: Stone::Flat }
+            } else {
+                Place { point: Point::new(0, board.size() - 1), stone: Stone::Flat }
             }
-            evolving = evolving_next;
+        } else {
+            Place { point: Point::new(0, 0), stone: Stone::Flat }
         }
-        println!("{:?}", evolving);
-        evolving
     }
-}
-
-#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
-struct Drops {
-    last_index: usize,
-    contents: Vec<usize>,
-}
 
-fn add_to_last(v: &Drops) -> Drops {
-    let mut vec = (*v).clone();
-    vec.contents[vec.last_index] += 1;
-    vec
-}
+    pub fn possible_moves<T: Board>(&self, board: &T) -> Vec<Turn> {
+        self.moves(board, PLACEMENTS_FIRST).collect()
+    }
 
-fn add_to_length(v: &Drops) -> Option<Drops>{
-    let mut vec = v.clone();
-    if vec.last_index < vec.contents.len() - 1 {
-        vec.contents[vec.last_index + 1] = 1;
-        vec.last_index += 1;
-        Some(vec)
-    } else {
-        None
+    /// Lazily generates moves in the given phase order
+    pub fn moves<'a, T: Board>(&self, board: &'a T, order: &'a [Phase]) -> MoveIter<'a, T> {
+        MoveIter::new(board, self.player, order)
     }
 }
 
-pub fn advantage(board: NaiveBoard) -> i64 {
-//Relevant stats
-//flat score
-//road options by length (how to compute?)
-//towers controlled (by num allied pieces in carry limit)
-    0
+/// Scores the board from player one's point of view
+pub fn advantage(board: &NaiveBoard) -> i64 {
+    Breakdown::new(board, &Weights::default()).score()
 }
-
-
-
-
@@ -0,0 +1,244 @@
+use std::cmp;
+
+use board::Board;
+use piece::Piece;
+use piece::Player;
+use piece::Stone;
+use point::Point;
+use turn::Direction;
+use turn::Turn;
+
+/// A group of moves, used to control the order moves are generated in
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Phase {
+    /// Slides that drop onto an opponent's stack or flatten a wall
+    Captures,
+    /// Every other slide
+    Slides,
+    Flats,
+    Walls,
+    Capstones,
+}
+
+/// Placements before slides, the order `Ai::possible_moves` has always used
+pub static PLACEMENTS_FIRST: &'static [Phase] = &[Phase::Flats, Phase::Walls, Phase::Capstones,
+                                                   Phase::Captures, Phase::Slides];
+
+/// Captures first, which tends to give earlier cutoffs in search
+pub static CAPTURES_FIRST: &'static [Phase] = &[Phase::Captures, Phase::Flats, Phase::Capstones,
+                                                 Phase::Slides, Phase::Walls];
+
+/// The first two turns only allow flat placements
+pub static OPENING: &'static [Phase] = &[Phase::Flats];
+
+const DIRECTIONS: [Direction; 4] = [Direction::Right, Direction::Left, Direction::Down, Direction::Up];
+
+// Where the iterator is within the current square
+#[derive(Clone, Copy, Debug)]
+struct Cursor {
+    started: bool,
+    dir: usize,
+    pieces: usize,
+    mask: usize,
+    // Filled in when the square is first visited
+    height: usize,
+    capstone: bool,
+    movable: bool,
+    // Filled in for each direction
+    clear: usize,
+    flatten: bool,
+}
+
+impl Cursor {
+    fn new() -> Cursor {
+        Cursor {
+            started: false,
+            dir: 0,
+            pieces: 1,
+            mask: 0,
+            height: 0,
+            capstone: false,
+            movable: false,
+            clear: 0,
+            flatten: false,
+        }
+    }
+}
+
+/// Lazily generates the moves for a player, one phase at a time
+///
+/// Nothing is collected up front, so a search that stops early after a
+/// cutoff doesn't pay for the rest of the moves. Slide drop patterns are
+/// walked as bitmasks over the gaps between carried pieces; only the
+/// yielded `Turn` owns its drops.
+pub struct MoveIter<'a, T: Board + 'a> {
+    board: &'a T,
+    player: Player,
+    order: &'a [Phase],
+    phase: usize,
+    square: usize,
+    cursor: Cursor,
+}
+
+impl<'a, T: Board + 'a> MoveIter<'a, T> {
+    pub fn new(board: &'a T, player: Player, order: &'a [Phase]) -> MoveIter<'a, T> {
+        MoveIter {
+            board: board,
+            player: player,
+            order: order,
+            phase: 0,
+            square: 0,
+            cursor: Cursor::new(),
+        }
+    }
+
+    fn point(&self) -> Point {
+        let size = self.board.size();
+        Point::new(self.square / size, self.square % size)
+    }
+
+    fn place(&mut self, stone: Stone) -> Option<Turn> {
+        if self.cursor.started {
+            return None;
+        }
+        self.cursor.started = true;
+        let point = self.point();
+        if self.board.at(&point).unwrap().next().is_some() {
+            return None;
+        }
+        let reserve = if stone == Stone::Capstone { Stone::Capstone } else { Stone::Flat };
+        if self.board.count().used_up(&Piece::new(reserve, self.player)) {
+            return None;
+        }
+        Some(Turn::Place { point: point, stone: stone })
+    }
+
+    fn start_square(&mut self) {
+        let pile = self.board.at(&self.point()).unwrap();
+        self.cursor.height = pile.clone().count();
+        self.cursor.movable = pile.mover() == Some(self.player);
+        self.cursor.capstone = pile.last().map(|piece| piece.stone()) == Some(Stone::Capstone);
+        self.cursor.started = true;
+        self.start_direction();
+    }
+
+    fn start_direction(&mut self) {
+        self.cursor.pieces = 1;
+        self.cursor.mask = 0;
+        self.cursor.clear = 0;
+        self.cursor.flatten = false;
+        if self.cursor.dir >= DIRECTIONS.len() {
+            return;
+        }
+        let point = self.point();
+        let dir = DIRECTIONS[self.cursor.dir];
+        let size = self.board.size();
+        while let Some(next) = dir.adjust(&point, self.cursor.clear + 1, size) {
+            if let Some(piece) = self.board.at(&next).unwrap().last() {
+                self.cursor.flatten = self.cursor.capstone && piece.stone() == Stone::Standing;
+                if piece.stone() != Stone::Flat {
+                    break;
+                }
+            }
+            self.cursor.clear += 1;
+        }
+    }
+
+    // Steps to the next drop pattern, moving on to more pieces and then
+    // the next direction as each runs out
+    fn advance(&mut self) {
+        self.cursor.mask += 1;
+        if self.cursor.mask >= 1 << (self.cursor.pieces - 1) {
+            self.cursor.mask = 0;
+            self.cursor.pieces += 1;
+        }
+        let carry = cmp::min(self.cursor.height, self.board.size());
+        if self.cursor.pieces > carry {
+            self.cursor.dir += 1;
+            self.start_direction();
+        }
+    }
+
+    fn slide(&mut self, captures: bool) -> Option<Turn> {
+        if !self.cursor.started {
+            self.start_square();
+        }
+        if !self.cursor.movable {
+            return None;
+        }
+        let point = self.point();
+        let size = self.board.size();
+        while self.cursor.dir < DIRECTIONS.len() {
+            let pieces = self.cursor.pieces;
+            let mask = self.cursor.mask;
+            let dir = DIRECTIONS[self.cursor.dir];
+            let clear = self.cursor.clear;
+            let flatten = self.cursor.flatten;
+            self.advance();
+
+            if pieces > self.cursor.height || (clear == 0 && !flatten) {
+                continue;
+            }
+
+            // Split the carried pieces wherever the mask has a bit set
+            let mut drops = [0; 8];
+            let mut len = 0;
+            for i in 0..pieces {
+                drops[len] += 1;
+                if mask & (1 << i) != 0 {
+                    len += 1;
+                }
+            }
+            len += 1;
+
+            let flattens = len == clear + 1;
+            if len > clear + 1 || (flattens && (!flatten || drops[len - 1] != 1)) {
+                continue;
+            }
+            let capture = flattens || (1..len + 1).any(|offset| {
+                let target = dir.adjust(&point, offset, size).unwrap();
+                let mover = self.board.at(&target).unwrap().mover();
+                mover.is_some() && mover != Some(self.player)
+            });
+            if capture != captures {
+                continue;
+            }
+            return Some(Turn::Slide {
+                num_pieces: pieces,
+                point: point,
+                direction: dir,
+                drops: drops[..len].to_vec(),
+            });
+        }
+        None
+    }
+}
+
+impl<'a, T: Board + 'a> Iterator for MoveIter<'a, T> {
+    type Item = Turn;
+
+    fn next(&mut self) -> Option<Turn> {
+        let squares = self.board.size() * self.board.size();
+        while self.phase < self.order.len() {
+            if self.square >= squares {
+                self.phase += 1;
+                self.square = 0;
+                self.cursor = Cursor::new();
+                continue;
+            }
+            let turn = match self.order[self.phase] {
+                Phase::Flats => self.place(Stone::Flat),
+                Phase::Walls => self.place(Stone::Standing),
+                Phase::Capstones => self.place(Stone::Capstone),
+                Phase::Captures => self.slide(true),
+                Phase::Slides => self.slide(false),
+            };
+            if turn.is_some() {
+                return turn;
+            }
+            self.square += 1;
+            self.cursor = Cursor::new();
+        }
+        None
+    }
+}
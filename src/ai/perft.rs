@@ -0,0 +1,53 @@
+use game::Game;
+use turn::Turn;
+
+/// Known leaf counts from the opening position, indexed by depth, for each
+/// board size from 3 up
+pub static REFERENCE: [(usize, &'static [u64]); 6] = [
+    (3, &[1, 9, 72, 1200, 17792, 271812, 3712952]),
+    (4, &[1, 16, 240, 7440, 216464, 6468872]),
+    (5, &[1, 25, 600, 43320, 2999784, 187855252]),
+    (6, &[1, 36, 1260, 132720, 13586048]),
+    (7, &[1, 49, 2352, 339696, 48051008]),
+    (8, &[1, 64, 4032, 764064, 142512320]),
+];
+
+/// Reference counts for a board size, if it's in the table
+pub fn reference(size: usize) -> Option<&'static [u64]> {
+    REFERENCE.iter().find(|&&(s, _)| s == size).map(|&(_, counts)| counts)
+}
+
+/// Counts the positions reachable in exactly depth plies
+///
+/// Finished games aren't played any further, so they only count when
+/// they end exactly at depth.
+pub fn perft(game: &Game, depth: usize) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+    if game.check_winner().is_some() {
+        return 0;
+    }
+    let moves = game.legal_moves();
+    if depth == 1 {
+        return moves.len() as u64;
+    }
+    moves.into_iter().map(|turn| {
+        let mut next = game.clone();
+        next.play_turn(turn).unwrap();
+        perft(&next, depth - 1)
+    }).fold(0, |sum, x| sum + x)
+}
+
+/// Splits the perft count by first move, for narrowing down a bad count
+pub fn perft_divide(game: &Game, depth: usize) -> Vec<(Turn, u64)> {
+    if depth == 0 || game.check_winner().is_some() {
+        return vec![];
+    }
+    game.legal_moves().into_iter().map(|turn| {
+        let mut next = game.clone();
+        next.play_turn(turn.clone()).unwrap();
+        let count = perft(&next, depth - 1);
+        (turn, count)
+    }).collect()
+}
@@ -0,0 +1,79 @@
+use game::Game;
+use piece::Player;
+use turn::Turn;
+
+/// Looks for a forced road win for the player to move within `plies` plies
+///
+/// Returns the shortest winning line found, where the defending moves are
+/// the ones that hold out longest. Every legal defense is tried, including
+/// walls and capstone flattening, so `None` means no forced road win exists
+/// at that depth. Flat wins don't count as solutions.
+pub fn solve(game: &Game, plies: usize) -> Option<Vec<Turn>> {
+    let attacker = game.next_player();
+    let mut depth = 1;
+    while depth <= plies {
+        if let Some(line) = attack(game, depth, attacker) {
+            return Some(line);
+        }
+        depth += 2;
+    }
+    None
+}
+
+// Some(winner) if the game ended, where None means someone won on flats
+fn outcome(game: &Game) -> Option<Option<Player>> {
+    match game.check_road_winner() {
+        Some(player) => Some(Some(player)),
+        None => game.check_flat_winner().map(|_| None),
+    }
+}
+
+fn attack(game: &Game, plies: usize, attacker: Player) -> Option<Vec<Turn>> {
+    for turn in game.legal_moves() {
+        let mut next = game.clone();
+        if next.play_turn(turn.clone()).is_err() {
+            continue;
+        }
+        match outcome(&next) {
+            Some(Some(winner)) if winner == attacker => return Some(vec![turn]),
+            Some(_) => continue,
+            None => {}
+        }
+        if plies >= 3 {
+            if let Some(line) = defend(&next, plies - 1, attacker) {
+                let mut full = vec![turn];
+                full.extend(line);
+                return Some(full);
+            }
+        }
+    }
+    None
+}
+
+fn defend(game: &Game, plies: usize, attacker: Player) -> Option<Vec<Turn>> {
+    let mut longest: Option<Vec<Turn>> = None;
+    for turn in game.legal_moves() {
+        let mut next = game.clone();
+        if next.play_turn(turn.clone()).is_err() {
+            continue;
+        }
+        let line = match outcome(&next) {
+            Some(Some(winner)) if winner == attacker => vec![turn],
+            Some(_) => return None,
+            None => {
+                match attack(&next, plies - 1, attacker) {
+                    Some(rest) => {
+                        let mut full = vec![turn];
+                        full.extend(rest);
+                        full
+                    }
+                    None => return None,
+                }
+            }
+        };
+        if longest.as_ref().map(|l| line.len() > l.len()).unwrap_or(true) {
+            longest = Some(line);
+        }
+    }
+    longest
+}
@@ -0,0 +1,372 @@
+use std::sync::Mutex;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::time::Instant;
+
+use ai::eval::Breakdown;
+use ai::eval::Weights;
+use ai::moves::CAPTURES_FIRST;
+use ai::moves::PLACEMENTS_FIRST;
+use ai::tablebase::Tablebase;
+use ai::tablebase::Value;
+use game::Game;
+use piece::Player;
+use turn::Turn;
+
+/// Score of a won game, less the number of plies it takes to get there
+pub const WIN: i64 = 1_000_000;
+
+/// Any score at least this close to WIN is a forced result
+pub const FORCED: i64 = WIN - 10_000;
+
+/// Slots in `TranspositionTable::new`
+pub const DEFAULT_SLOTS: usize = 1 << 16;
+
+// Each lock covers every STRIPES-th slot, so threads rarely wait on each
+// other
+const STRIPES: usize = 64;
+
+// Forced results are counted from the root while searching, but stored
+// counted from the position itself so that they stay right when the same
+// position turns up at another ply
+fn to_table(score: i64, ply: usize) -> i64 {
+    if score >= FORCED {
+        score + ply as i64
+    } else if score <= -FORCED {
+        score - ply as i64
+    } else {
+        score
+    }
+}
+
+fn from_table(score: i64, ply: usize) -> i64 {
+    if score >= FORCED {
+        score - ply as i64
+    } else if score <= -FORCED {
+        score + ply as i64
+    } else {
+        score
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Bound {
+    Exact,
+    Lower,
+    Upper,
+}
+
+#[derive(Clone, Debug)]
+struct Entry {
+    depth: usize,
+    score: i64,
+    bound: Bound,
+    best: Option<Turn>,
+}
+
+/// Search results keyed by `Game::position_hash`, shared between threads
+///
+/// The table has a fixed number of slots, picked by hash, and a slot keeps
+/// whichever of its results was searched deeper.
+pub struct TranspositionTable {
+    stripes: Vec<Mutex<Vec<Option<(u64, Entry)>>>>,
+    slots: usize,
+}
+
+impl TranspositionTable {
+    pub fn new() -> TranspositionTable {
+        TranspositionTable::with_slots(DEFAULT_SLOTS)
+    }
+
+    /// A table with room for about this many results
+    pub fn with_slots(slots: usize) -> TranspositionTable {
+        let per_stripe = (slots + STRIPES - 1) / STRIPES;
+        TranspositionTable {
+            stripes: (0..STRIPES).map(|_| Mutex::new(vec![None; per_stripe])).collect(),
+            slots: per_stripe * STRIPES,
+        }
+    }
+
+    /// The number of slots in use
+    pub fn len(&self) -> usize {
+        self.stripes.iter().map(|stripe| {
+            stripe.lock().unwrap().iter().filter(|slot| slot.is_some()).count()
+        }).fold(0, |sum, x| sum + x)
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.slots
+    }
+
+    /// Follows the best moves stored for each position, starting from game
+    pub fn principal_variation(&self, game: &Game, max: usize) -> Vec<Turn> {
+        let mut game = game.clone();
+        let mut pv = vec![];
+        while pv.len() < max && game.check_winner().is_none() {
+            let turn = match self.get(game.position_hash()).and_then(|e| e.best) {
+                Some(turn) => turn,
+                None => break,
+            };
+            if game.play_turn(turn.clone()).is_err() {
+                break;
+            }
+            pv.push(turn);
+        }
+        pv
+    }
+
+    fn slot(&self, hash: u64) -> (usize, usize) {
+        let slot = (hash % self.slots as u64) as usize;
+        (slot % STRIPES, slot / STRIPES)
+    }
+
+    fn get(&self, hash: u64) -> Option<Entry> {
+        let (stripe, index) = self.slot(hash);
+        match self.stripes[stripe].lock().unwrap()[index] {
+            Some((key, ref entry)) if key == hash => Some(entry.clone()),
+            _ => None,
+        }
+    }
+
+    fn store(&self, hash: u64, entry: Entry) {
+        let (stripe, index) = self.slot(hash);
+        let mut slots = self.stripes[stripe].lock().unwrap();
+        let replace = match slots[index] {
+            Some((_, ref old)) => old.depth <= entry.depth,
+            None => true,
+        };
+        if replace {
+            slots[index] = Some((hash, entry));
+        }
+    }
+}
+
+/// A single thread's negamax search
+///
+/// Helper threads (any id other than 0) give up as soon as `stop` is set,
+/// the main thread once it has finished its first iteration, and odd numbered helpers order moves differently so that they don't all
+/// search the same lines.
+pub struct Searcher<'a> {
+    table: &'a TranspositionTable,
+    stop: &'a AtomicBool,
+    id: usize,
+    deadline: Option<Instant>,
+    timed: bool,
+    weights: Weights,
+    tablebase: Option<&'a Tablebase>,
+    root_best: Option<Turn>,
+    pub nodes: u64,
+}
+
+impl<'a> Searcher<'a> {
+    pub fn new(table: &'a TranspositionTable, stop: &'a AtomicBool, id: usize) -> Searcher<'a> {
+        Searcher {
+            table: table,
+            stop: stop,
+            id: id,
+            deadline: None,
+            timed: false,
+            weights: Weights::default(),
+            tablebase: None,
+            root_best: None,
+            nodes: 0,
+        }
+    }
+
+    /// Stops deepening once the deadline passes. The first iteration always
+    /// finishes so that there's a move to play.
+    pub fn with_deadline(mut self, deadline: Option<Instant>) -> Searcher<'a> {
+        self.deadline = deadline;
+        self
+    }
+
+    /// Evaluates positions with these weights instead of the defaults
+    pub fn with_weights(mut self, weights: Weights) -> Searcher<'a> {
+        self.weights = weights;
+        self
+    }
+
+    /// Scores solved endgame positions from the tablebase instead of
+    /// searching them
+    pub fn with_tablebase(mut self, tablebase: Option<&'a Tablebase>) -> Searcher<'a> {
+        self.tablebase = tablebase;
+        self
+    }
+
+    fn aborted(&self) -> bool {
+        if (self.id != 0 || self.timed) && self.stop.load(Ordering::Relaxed) {
+            return true;
+        }
+        self.timed && self.deadline.map(|d| Instant::now() >= d).unwrap_or(false)
+    }
+
+    /// Iterative deepening up to depth, returning the best move and its
+    /// score for the player to move
+    ///
+    /// The move comes from this searcher's own root search, never from the
+    /// shared table, so it always goes with the score.
+    pub fn iterate(&mut self, game: &Game, depth: usize) -> (Option<Turn>, i64) {
+        let mut result = (None, 0);
+        for d in 1..depth + 1 {
+            let (turn, score) = match self.root(game, d) {
+                Some(best) => best,
+                None => break,
+            };
+            result = (Some(turn), score);
+            if score.abs() >= WIN - d as i64 {
+                // Found a forced result, no need to look deeper
+                break;
+            }
+        }
+        result
+    }
+
+    /// One iteration of iterative deepening, returning the best move and
+    /// its score, or `None` if the search was cut short
+    ///
+    /// Callers deepen one step at a time, so that they can report each
+    /// finished depth.
+    pub fn root(&mut self, game: &Game, depth: usize) -> Option<(Turn, i64)> {
+        self.root_best = None;
+        let score = self.negamax(game, depth, -WIN - 1, WIN + 1, 0);
+        if self.aborted() {
+            return None;
+        }
+        self.timed = true;
+        self.root_best.take().map(|turn| (turn, score))
+    }
+
+    /// Iterative deepening that scores every root move exactly, best first,
+    /// along with the depth of the last finished iteration
+    pub fn iterate_scored(&mut self, game: &Game, depth: usize) -> (Vec<(Turn, i64)>, usize) {
+        let mut result = (vec![], 0);
+        for d in 1..depth + 1 {
+            let scores = self.score_moves(game, d);
+            if self.aborted() {
+                break;
+            }
+            result = (scores, d);
+            self.timed = true;
+            if result.0.first().map(|&(_, score)| score.abs() >= WIN - d as i64).unwrap_or(true) {
+                break;
+            }
+        }
+        result
+    }
+
+    /// Searches every root move with a full window, so each score is exact
+    pub fn score_moves(&mut self, game: &Game, depth: usize) -> Vec<(Turn, i64)> {
+        let mover = game.next_player();
+        let mut scores = vec![];
+        for turn in game.legal_moves() {
+            let mut next = game.clone();
+            let score = match next.play_turn(turn.clone()) {
+                Err(_) => continue,
+                Ok(Some(winner)) if winner == mover => WIN - 1,
+                Ok(Some(_)) => -(WIN - 1),
+                Ok(None) => -self.negamax(&next, depth - 1, -WIN - 1, WIN + 1, 1),
+            };
+            if self.aborted() {
+                break;
+            }
+            scores.push((turn, score));
+        }
+        scores.sort_by(|a, b| b.1.cmp(&a.1));
+        scores
+    }
+
+    fn negamax(&mut self, game: &Game, depth: usize, mut alpha: i64, beta: i64, ply: usize) -> i64 {
+        self.nodes += 1;
+        let hash = game.position_hash();
+        let original_alpha = alpha;
+
+        let mut first = None;
+        if let Some(entry) = self.table.get(hash) {
+            if entry.depth >= depth && ply > 0 {
+                let score = from_table(entry.score, ply);
+                match entry.bound {
+                    Bound::Exact => return score,
+                    Bound::Lower if score >= beta => return score,
+                    Bound::Upper if score <= alpha => return score,
+                    _ => {}
+                }
+            }
+            first = entry.best;
+        }
+
+        // The root still needs a move, so it's always searched
+        if ply > 0 {
+            match self.tablebase.and_then(|t| t.probe(game)) {
+                Some(Value::Win(d)) => return WIN - (ply + d as usize) as i64,
+                Some(Value::Loss(d)) => return -(WIN - (ply + d as usize) as i64),
+                Some(Value::Draw) => return 0,
+                None => {}
+            }
+        }
+
+        if depth == 0 {
+            return evaluate(game, &self.weights);
+        }
+
+        let mover = game.next_player();
+        let mut best_score = -WIN - 1;
+        let mut best = None;
+        let order = if self.id % 2 == 1 { PLACEMENTS_FIRST } else { CAPTURES_FIRST };
+        let skip = first.clone();
+        let moves = first.into_iter()
+                         .chain(game.moves(order).filter(move |turn| skip.as_ref() != Some(turn)));
+        for turn in moves {
+            let mut next = game.clone();
+            let score = match next.play_turn(turn.clone()) {
+                Err(_) => continue,
+                Ok(Some(winner)) if winner == mover => WIN - ply as i64 - 1,
+                Ok(Some(_)) => -(WIN - ply as i64 - 1),
+                Ok(None) => -self.negamax(&next, depth - 1, -beta, -alpha, ply + 1),
+            };
+            if self.aborted() {
+                return 0;
+            }
+            if score > best_score {
+                best_score = score;
+                best = Some(turn);
+            }
+            if score > alpha {
+                alpha = score;
+            }
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        if best.is_none() {
+            return evaluate(game, &self.weights);
+        }
+
+        let bound = if best_score <= original_alpha {
+            Bound::Upper
+        } else if best_score >= beta {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+        if ply == 0 {
+            self.root_best = best.clone();
+        }
+        self.table.store(hash, Entry {
+            depth: depth,
+            score: to_table(best_score, ply),
+            bound: bound,
+            best: best,
+        });
+        best_score
+    }
+}
+
+/// Static evaluation for the player to move
+pub fn evaluate(game: &Game, weights: &Weights) -> i64 {
+    let score = Breakdown::new(game.board(), weights).score();
+    match game.next_player() {
+        Player::One => score,
+        Player::Two => -score,
+    }
+}
@@ -0,0 +1,50 @@
+use ai::Ai;
+use ai::rng::Rng;
+use game::Game;
+use turn::Turn;
+
+/// Anything that can pick a move, so that opponents can be swapped freely
+///
+/// `choose` is only called when the game isn't over and it's the
+/// strategy's turn, and must return a legal move.
+pub trait Strategy {
+    fn choose(&mut self, game: &Game) -> Turn;
+}
+
+impl Strategy for Ai {
+    fn choose(&mut self, game: &Game) -> Turn {
+        self.best_move(game)
+    }
+}
+
+impl<'a, S: Strategy + ?Sized> Strategy for &'a mut S {
+    fn choose(&mut self, game: &Game) -> Turn {
+        (**self).choose(game)
+    }
+}
+
+impl<S: Strategy + ?Sized> Strategy for Box<S> {
+    fn choose(&mut self, game: &Game) -> Turn {
+        (**self).choose(game)
+    }
+}
+
+/// Plays a uniformly random legal move, as a baseline opponent
+#[derive(Clone, Debug)]
+pub struct RandomMover {
+    rng: Rng,
+}
+
+impl RandomMover {
+    pub fn new(seed: u64) -> RandomMover {
+        RandomMover { rng: Rng::new(seed) }
+    }
+}
+
+impl Strategy for RandomMover {
+    fn choose(&mut self, game: &Game) -> Turn {
+        let mut moves = game.legal_moves();
+        let index = self.rng.below(moves.len());
+        moves.swap_remove(index)
+    }
+}
@@ -0,0 +1,327 @@
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io;
+use std::io::Read;
+use std::io::Write;
+use std::path::Path;
+
+use board::Board;
+use game::Game;
+use piece::Player;
+
+const MAGIC: &'static [u8] = b"TAKTB";
+const VERSION: u8 = 1;
+
+/// The game theoretic value of a position for the player to move, with the
+/// number of plies to the end of the game under best play
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Value {
+    Win(u16),
+    Loss(u16),
+    /// Neither player can force a win, so the game goes on forever
+    Draw,
+}
+
+/// Which positions a tablebase covers
+///
+/// Slides can empty squares again, so positions are explored forward from
+/// the seeds, and any move that leaves these limits makes the value of the
+/// position it came from unknown (unless another move decides it anyway).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TablebaseLimits {
+    pub max_empty: usize,
+    /// Flats and capstones left, for each player
+    pub max_reserves: usize,
+    /// Stops exploring once this many positions have been found
+    pub max_positions: usize,
+}
+
+impl TablebaseLimits {
+    pub fn new(max_empty: usize, max_reserves: usize) -> TablebaseLimits {
+        TablebaseLimits {
+            max_empty: max_empty,
+            max_reserves: max_reserves,
+            max_positions: 1_000_000,
+        }
+    }
+
+    pub fn contains(&self, game: &Game) -> bool {
+        let board = game.board();
+        let empty = board.squares().into_iter().filter(|square| square.clone().next().is_none()).count();
+        let count = board.count();
+        let reserves = |player| match player {
+            Player::One => count.max_flat + count.max_cap - count.p1_flat - count.p1_cap,
+            Player::Two => count.max_flat + count.max_cap - count.p2_flat - count.p2_cap,
+        };
+        game.turn_number() >= 2 && empty <= self.max_empty &&
+        reserves(Player::One) <= self.max_reserves && reserves(Player::Two) <= self.max_reserves
+    }
+}
+
+// What a move from an explored position leads to
+#[derive(Clone, Copy, Debug)]
+enum Child {
+    Position(usize),
+    Over(Value),
+    Unknown,
+}
+
+struct Node {
+    hash: u64,
+    children: Vec<Child>,
+    value: Option<Value>,
+}
+
+/// Solved endgame positions of one board size, keyed by
+/// `Game::position_hash`
+///
+/// Meant for 3x3 and 4x4, where endgames with few empty squares and small
+/// reserves are small enough to solve completely.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tablebase {
+    size: usize,
+    entries: HashMap<u64, Value>,
+}
+
+impl Tablebase {
+    pub fn new(size: usize) -> Tablebase {
+        Tablebase {
+            size: size,
+            entries: HashMap::new(),
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn probe(&self, game: &Game) -> Option<Value> {
+        if game.size() != self.size {
+            return None;
+        }
+        self.entries.get(&game.position_hash()).cloned()
+    }
+
+    /// Solves every position reachable from the seeds within the limits
+    ///
+    /// Positions are labeled backwards from the finished games, one distance
+    /// at a time, so every distance is the shortest win or the longest loss.
+    /// Only positions whose value is certain are kept: a draw needs every
+    /// line from it to stay inside the limits.
+    pub fn generate(size: usize, seeds: &[Game], limits: &TablebaseLimits) -> Tablebase {
+        let mut nodes = explore(size, seeds, limits);
+
+        let mut distance = 1;
+        loop {
+            let mut solved = vec![];
+            for (index, node) in nodes.iter().enumerate() {
+                if node.value.is_none() {
+                    if let Some(value) = solve(node, &nodes, distance) {
+                        solved.push((index, value));
+                    }
+                }
+            }
+            if solved.is_empty() {
+                break;
+            }
+            for (index, value) in solved {
+                nodes[index].value = Some(value);
+            }
+            distance += 1;
+        }
+
+        // Unsolved positions are draws unless some line leads to an
+        // unknown position
+        let mut open = nodes.iter()
+                            .map(|node| {
+                                node.value.is_none() &&
+                                node.children.iter().any(|child| match *child {
+                                    Child::Unknown => true,
+                                    _ => false,
+                                })
+                            })
+                            .collect::<Vec<_>>();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for index in 0..nodes.len() {
+                if open[index] || nodes[index].value.is_some() {
+                    continue;
+                }
+                let reaches_open = nodes[index].children.iter().any(|child| match *child {
+                    Child::Position(next) => open[next],
+                    _ => false,
+                });
+                if reaches_open {
+                    open[index] = true;
+                    changed = true;
+                }
+            }
+        }
+
+        let mut tablebase = Tablebase::new(size);
+        for (index, node) in nodes.iter().enumerate() {
+            match node.value {
+                Some(value) => {
+                    tablebase.entries.insert(node.hash, value);
+                }
+                None if !open[index] => {
+                    tablebase.entries.insert(node.hash, Value::Draw);
+                }
+                None => {}
+            }
+        }
+        tablebase
+    }
+
+    /// Reads a tablebase written by `save`
+    ///
+    /// The format is the bytes `TAKTB`, a version byte, the board size
+    /// byte, a little endian u32 count and then for each position (sorted
+    /// by hash) the little endian u64 hash, a byte for draw (0), win (1) or
+    /// loss (2), and the little endian u16 distance.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Tablebase> {
+        let mut bytes = vec![];
+        try!(try!(File::open(path)).read_to_end(&mut bytes));
+        Tablebase::from_bytes(&bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut file = try!(File::create(path));
+        file.write_all(&self.to_bytes())
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(VERSION);
+        bytes.push(self.size as u8);
+        push_le(&mut bytes, self.entries.len() as u64, 4);
+        let mut entries = self.entries.iter().collect::<Vec<_>>();
+        entries.sort_by_key(|&(hash, _)| *hash);
+        for (hash, value) in entries {
+            push_le(&mut bytes, *hash, 8);
+            let (kind, distance) = match *value {
+                Value::Draw => (0, 0),
+                Value::Win(d) => (1, d),
+                Value::Loss(d) => (2, d),
+            };
+            bytes.push(kind);
+            push_le(&mut bytes, distance as u64, 2);
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Tablebase, String> {
+        let header = MAGIC.len() + 6;
+        if bytes.len() < header || &bytes[..MAGIC.len()] != MAGIC {
+            return Err("Not a tablebase".into());
+        }
+        if bytes[MAGIC.len()] != VERSION {
+            return Err(format!("Unsupported tablebase version {}", bytes[MAGIC.len()]));
+        }
+        let mut tablebase = Tablebase::new(bytes[MAGIC.len() + 1] as usize);
+        let count = read_le(&bytes[MAGIC.len() + 2..header]) as usize;
+        let entries = &bytes[header..];
+        if entries.len() != count * 11 {
+            return Err("Tablebase is truncated".into());
+        }
+        for entry in entries.chunks(11) {
+            let distance = read_le(&entry[9..11]) as u16;
+            let value = match entry[8] {
+                0 => Value::Draw,
+                1 => Value::Win(distance),
+                2 => Value::Loss(distance),
+                _ => return Err("Invalid tablebase value".into()),
+            };
+            tablebase.entries.insert(read_le(&entry[..8]), value);
+        }
+        Ok(tablebase)
+    }
+}
+
+// Finds every position reachable from the seeds without leaving the limits
+fn explore(size: usize, seeds: &[Game], limits: &TablebaseLimits) -> Vec<Node> {
+    let mut nodes: Vec<Node> = vec![];
+    let mut index = HashMap::new();
+    let mut queue = VecDeque::new();
+    for seed in seeds.iter() {
+        if seed.size() == size && seed.check_winner().is_none() && limits.contains(seed) &&
+           !index.contains_key(&seed.position_hash()) {
+            index.insert(seed.position_hash(), nodes.len());
+            queue.push_back((nodes.len(), seed.clone()));
+            nodes.push(Node { hash: seed.position_hash(), children: vec![], value: None });
+        }
+    }
+
+    while let Some((current, game)) = queue.pop_front() {
+        let mover = game.next_player();
+        let mut children = vec![];
+        for turn in game.legal_moves() {
+            let mut next = game.clone();
+            let child = match next.play_turn(turn) {
+                Err(_) => continue,
+                // The game is over, and the player to move there has lost
+                // (or won, if the last move handed them the game)
+                Ok(Some(winner)) if winner == mover => Child::Over(Value::Loss(0)),
+                Ok(Some(_)) => Child::Over(Value::Win(0)),
+                Ok(None) if !limits.contains(&next) => Child::Unknown,
+                Ok(None) => {
+                    let hash = next.position_hash();
+                    match index.get(&hash).cloned() {
+                        Some(known) => Child::Position(known),
+                        None if nodes.len() >= limits.max_positions => Child::Unknown,
+                        None => {
+                            index.insert(hash, nodes.len());
+                            queue.push_back((nodes.len(), next));
+                            nodes.push(Node { hash: hash, children: vec![], value: None });
+                            Child::Position(nodes.len() - 1)
+                        }
+                    }
+                }
+            };
+            children.push(child);
+        }
+        nodes[current].children = children;
+    }
+    nodes
+}
+
+// The value of a node at exactly this distance, given that every node
+// closer to the end of the game is already solved
+fn solve(node: &Node, nodes: &[Node], distance: u16) -> Option<Value> {
+    let child_value = |child: &Child| match *child {
+        Child::Position(next) => nodes[next].value,
+        Child::Over(value) => Some(value),
+        Child::Unknown => None,
+    };
+    let wins = node.children.iter().any(|child| child_value(child) == Some(Value::Loss(distance - 1)));
+    if wins {
+        return Some(Value::Win(distance));
+    }
+    let mut longest = None;
+    for child in node.children.iter() {
+        match child_value(child) {
+            Some(Value::Win(d)) => longest = Some(longest.map_or(d, |l: u16| l.max(d))),
+            _ => return None,
+        }
+    }
+    match longest {
+        Some(d) if d == distance - 1 => Some(Value::Loss(distance)),
+        _ => None,
+    }
+}
+
+fn push_le(bytes: &mut Vec<u8>, value: u64, width: usize) {
+    for i in 0..width {
+        bytes.push((value >> (8 * i)) as u8);
+    }
+}
+
+fn read_le(bytes: &[u8]) -> u64 {
+    bytes.iter().rev().fold(0, |value, &byte| value << 8 | byte as u64)
+}
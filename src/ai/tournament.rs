@@ -0,0 +1,187 @@
+use std::f64;
+use std::fmt;
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::Write;
+use std::path::PathBuf;
+
+use ai::strategy::Strategy;
+use game::Game;
+use piece::Player;
+use turn::Turn;
+
+/// Elo difference implied by a score between 0 and 1
+pub fn elo_difference(score: f64) -> f64 {
+    if score <= 0.0 {
+        return f64::NEG_INFINITY;
+    }
+    if score >= 1.0 {
+        return f64::INFINITY;
+    }
+    -400.0 * (1.0 / score - 1.0).log10()
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Win,
+    Loss,
+    Draw,
+}
+
+/// Results from the first engine's point of view, with every game as PTN
+#[derive(Clone, Debug)]
+pub struct MatchResult {
+    pub wins: usize,
+    pub losses: usize,
+    pub draws: usize,
+    pub games: Vec<String>,
+}
+
+impl MatchResult {
+    pub fn played(&self) -> usize {
+        self.wins + self.losses + self.draws
+    }
+
+    /// Points per game, counting draws as half
+    pub fn score(&self) -> f64 {
+        if self.played() == 0 {
+            return 0.5;
+        }
+        (self.wins as f64 + self.draws as f64 / 2.0) / self.played() as f64
+    }
+
+    /// Elo difference and the half width of its 95% confidence interval
+    pub fn elo(&self) -> (f64, f64) {
+        let n = self.played() as f64;
+        let score = self.score();
+        if n == 0.0 {
+            return (0.0, f64::INFINITY);
+        }
+        let variance = (self.wins as f64 * (1.0 - score).powi(2) +
+                        self.draws as f64 * (0.5 - score).powi(2) +
+                        self.losses as f64 * score.powi(2)) / n;
+        let error = 1.96 * (variance / n).sqrt();
+        let low = elo_difference(score - error);
+        let high = elo_difference(score + error);
+        (elo_difference(score), (high - low) / 2.0)
+    }
+}
+
+impl fmt::Display for MatchResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (elo, error) = self.elo();
+        write!(f, "+{} -{} ={}, Elo difference {:.1} +/- {:.1}",
+               self.wins, self.losses, self.draws, elo, error)
+    }
+}
+
+/// Plays two strategies against each other
+///
+/// Each starting position is played twice, once with each strategy moving
+/// first. Games longer than `max_plies` are drawn.
+pub struct Match {
+    first: Box<Strategy>,
+    second: Box<Strategy>,
+    size: usize,
+    games: usize,
+    openings: Vec<Vec<Turn>>,
+    max_plies: usize,
+    ptn_dir: Option<PathBuf>,
+}
+
+impl Match {
+    pub fn new<A: Strategy + 'static, B: Strategy + 'static>(first: A, second: B, size: usize,
+                                                             games: usize) -> Match {
+        Match {
+            first: Box::new(first),
+            second: Box::new(second),
+            size: size,
+            games: games,
+            openings: vec![vec![]],
+            max_plies: 200,
+            ptn_dir: None,
+        }
+    }
+
+    /// Starts games from these move sequences, in turn
+    pub fn with_openings(mut self, openings: Vec<Vec<Turn>>) -> Match {
+        if !openings.is_empty() {
+            self.openings = openings;
+        }
+        self
+    }
+
+    pub fn with_max_plies(mut self, plies: usize) -> Match {
+        self.max_plies = plies;
+        self
+    }
+
+    /// Writes each game to `game-<number>.ptn` in the directory
+    pub fn with_ptn_dir(mut self, dir: PathBuf) -> Match {
+        self.ptn_dir = Some(dir);
+        self
+    }
+
+    pub fn run(&mut self) -> io::Result<MatchResult> {
+        let mut result = MatchResult {
+            wins: 0,
+            losses: 0,
+            draws: 0,
+            games: vec![],
+        };
+        if let Some(ref dir) = self.ptn_dir {
+            try!(fs::create_dir_all(dir));
+        }
+
+        for number in 0..self.games {
+            let opening = self.openings[(number / 2) % self.openings.len()].clone();
+            let first_moves_first = number % 2 == 0;
+            let (outcome, game) = try!(self.play(&opening, first_moves_first));
+            match outcome {
+                Outcome::Win => result.wins += 1,
+                Outcome::Loss => result.losses += 1,
+                Outcome::Draw => result.draws += 1,
+            }
+
+            let ptn = if first_moves_first {
+                game.as_ptn_with_players("first", "second")
+            } else {
+                game.as_ptn_with_players("second", "first")
+            };
+            if let Some(ref dir) = self.ptn_dir {
+                let mut file = try!(File::create(dir.join(format!("game-{:03}.ptn", number + 1))));
+                try!(file.write_all(ptn.as_bytes()));
+            }
+            result.games.push(ptn);
+        }
+        Ok(result)
+    }
+
+    fn play(&mut self, opening: &[Turn], first_moves_first: bool) -> io::Result<(Outcome, Game)> {
+        let mut game = Game::new(self.size);
+        for turn in opening {
+            try!(game.play_turn(turn.clone())
+                     .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e)));
+        }
+        let first_player = if first_moves_first { Player::One } else { Player::Two };
+
+        let mut winner = game.check_winner();
+        while winner.is_none() && game.turn_number() < self.max_plies {
+            let turn = if game.next_player() == first_player {
+                self.first.choose(&game)
+            } else {
+                self.second.choose(&game)
+            };
+            winner = try!(game.play_turn(turn)
+                              .map_err(|e| io::Error::new(io::ErrorKind::Other, e)));
+        }
+
+        let outcome = match winner {
+            Some(player) if player == first_player => Outcome::Win,
+            Some(_) => Outcome::Loss,
+            None => Outcome::Draw,
+        };
+        Ok((outcome, game))
+    }
+}
@@ -0,0 +1,156 @@
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::Read;
+use std::path::Path;
+
+use ai::eval::Terms;
+use ai::eval::Weights;
+use game::Game;
+use piece::Player;
+use ptn::Ptn;
+use turn::Turn;
+
+/// Plies at the start of each game that aren't sampled
+const SKIP_PLIES: usize = 4;
+
+/// Scales scores into win probabilities: 400 points is ten to one odds
+const SCALE: f64 = 400.0;
+
+/// A quiet position's evaluation terms, and how the game turned out for
+/// player one (1 for a win, 0 for a loss, 0.5 for a draw)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sample {
+    pub player_one: Terms,
+    pub player_two: Terms,
+    pub result: f64,
+}
+
+impl Sample {
+    pub fn score(&self, weights: &Weights) -> i64 {
+        self.player_one.score(weights) - self.player_two.score(weights)
+    }
+}
+
+/// Player one's score for a PTN result, if the game finished
+pub fn result_value(result: &str) -> Option<f64> {
+    match result {
+        "R-0" | "F-0" | "1-0" => Some(1.0),
+        "0-R" | "0-F" | "0-1" => Some(0.0),
+        "1/2-1/2" => Some(0.5),
+        _ => None,
+    }
+}
+
+/// Whether neither player can finish a road next move, so the static
+/// evaluation says something about the position
+pub fn is_quiet(game: &Game) -> bool {
+    game.check_winner().is_none() && game.road_threats(Player::One).is_empty() &&
+    game.road_threats(Player::Two).is_empty()
+}
+
+/// Samples every quiet position of a game, labeled with its result
+pub fn samples_from_game(moves: &[Turn], size: usize, result: f64) -> Result<Vec<Sample>, String> {
+    let mut game = Game::new(size);
+    let mut samples = vec![];
+    for turn in moves {
+        try!(game.play_turn(turn.clone()));
+        if game.turn_number() >= SKIP_PLIES && is_quiet(&game) {
+            samples.push(Sample {
+                player_one: Terms::new(game.board(), Player::One),
+                player_two: Terms::new(game.board(), Player::Two),
+                result: result,
+            });
+        }
+    }
+    Ok(samples)
+}
+
+/// Samples every `.ptn` file in a directory
+///
+/// Games without a result, either tagged or reached on the board, and
+/// files that can't be parsed or replayed are skipped.
+pub fn samples_from_ptn_dir<P: AsRef<Path>>(dir: P) -> io::Result<Vec<Sample>> {
+    let mut samples = vec![];
+    for entry in try!(fs::read_dir(dir)) {
+        let path = try!(entry).path();
+        if path.extension().map(|e| e != "ptn").unwrap_or(true) {
+            continue;
+        }
+        let mut text = String::new();
+        try!(try!(File::open(&path)).read_to_string(&mut text));
+        let ptn = match text.parse::<Ptn>() {
+            Ok(ptn) => ptn,
+            Err(_) => continue,
+        };
+        let game = match ptn.game() {
+            Ok(game) => game,
+            Err(_) => continue,
+        };
+        let result = ptn.tag("Result").and_then(result_value).or(result_value(game.result()));
+        if let Some(result) = result {
+            if let Ok(mut game_samples) = samples_from_game(&ptn.moves, game.size(), result) {
+                samples.append(&mut game_samples);
+            }
+        }
+    }
+    Ok(samples)
+}
+
+/// Mean squared difference between the results and the win probabilities
+/// predicted from the weighted evaluation
+pub fn error(samples: &[Sample], weights: &Weights) -> f64 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    let total = samples.iter().fold(0.0, |sum, sample| {
+        let predicted = 1.0 / (1.0 + 10f64.powf(-sample.score(weights) as f64 / SCALE));
+        sum + (sample.result - predicted).powi(2)
+    });
+    total / samples.len() as f64
+}
+
+fn weight(weights: &mut Weights, index: usize) -> &mut i64 {
+    match index {
+        0 => &mut weights.flats,
+        1 => &mut weights.road_distance,
+        2 => &mut weights.reserves,
+        3 => &mut weights.stack_control,
+        4 => &mut weights.walls,
+        _ => &mut weights.capstones,
+    }
+}
+
+/// Texel style local search: nudges each weight up or down while that
+/// lowers the error, halving the step whenever nothing helps
+///
+/// Stops after `passes` passes over the weights, or once a step of one
+/// makes no improvement.
+pub fn tune(samples: &[Sample], start: Weights, passes: usize) -> Weights {
+    let mut best = start;
+    let mut best_error = error(samples, &best);
+    let mut step = 16;
+    for _ in 0..passes {
+        let mut improved = false;
+        for index in 0..6 {
+            for &delta in [step, -step].iter() {
+                let mut candidate = best;
+                *weight(&mut candidate, index) += delta;
+                let candidate_error = error(samples, &candidate);
+                if candidate_error < best_error {
+                    best = candidate;
+                    best_error = candidate_error;
+                    improved = true;
+                    break;
+                }
+            }
+        }
+        if !improved {
+            if step == 1 {
+                break;
+            }
+            step /= 2;
+        }
+    }
+    best
+}
@@ -0,0 +1,33 @@
+extern crate tak;
+
+use std::io;
+use std::io::BufRead;
+use std::sync::mpsc;
+use std::thread;
+
+use tak::tei::Tei;
+
+/// Speaks the Tak Engine Interface over stdin and stdout
+fn main() {
+    let (sender, receiver) = mpsc::channel::<String>();
+    let printer = thread::spawn(move || {
+        for line in receiver {
+            println!("{}", line);
+        }
+    });
+
+    let mut engine = Tei::new(sender);
+    let stdin = io::stdin();
+    for line in stdin.lock().lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(_) => break,
+        };
+        if !engine.handle(&line) {
+            break;
+        }
+    }
+    // Dropping the engine waits for any search and closes the channel
+    drop(engine);
+    printer.join().unwrap();
+}
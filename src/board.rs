@@ -1,3 +1,4 @@
+use std::cmp;
 use std::collections::VecDeque;
 use std::collections::BTreeSet;
 use std::fmt;
@@ -22,15 +23,15 @@ pub struct PieceCount {
 
 impl PieceCount {
     pub fn new(size: usize) -> PieceCount {
-        let flat_counts = [15, 21, 30, 40, 50];
-        let capstone_counts = [0, 1, 1, 2, 2];
+        let flat_counts = [10, 15, 21, 30, 40, 50];
+        let capstone_counts = [0, 0, 1, 1, 2, 2];
         PieceCount {
             p1_flat: 0,
             p1_cap: 0,
             p2_flat: 0,
             p2_cap: 0,
-            max_flat: flat_counts[size - 4],
-            max_cap: capstone_counts[size - 4],
+            max_flat: flat_counts[size - 3],
+            max_cap: capstone_counts[size - 3],
         }
     }
 
@@ -127,6 +128,24 @@ impl PieceIter {
     }
 }
 
+/// Minimum number of placements a player needs to finish a road, for each
+/// axis. None when the opponent's walls and capstones cut every path.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, RustcDecodable, RustcEncodable)]
+pub struct RoadDistance {
+    pub horizontal: Option<usize>,
+    pub vertical: Option<usize>,
+}
+
+impl RoadDistance {
+    /// Distance along whichever axis is closer to a road
+    pub fn min(&self) -> Option<usize> {
+        match (self.horizontal, self.vertical) {
+            (Some(h), Some(v)) => Some(cmp::min(h, v)),
+            (h, v) => h.or(v),
+        }
+    }
+}
+
 pub trait Board {
     fn new(usize) -> Self;
     fn size(&self) -> usize;
@@ -157,6 +176,18 @@ pub trait Board {
         connected
     }
 
+    /// Counts the placements player needs to connect opposite edges
+    ///
+    /// Squares that player already controls are free, empty squares,
+    /// opponent flats (which can be captured) and the player's own walls
+    /// each cost one, and opponent walls and capstones can't be crossed.
+    fn road_distance(&self, player: Player) -> RoadDistance {
+        RoadDistance {
+            horizontal: road_distance_along(self, player, true),
+            vertical: road_distance_along(self, player, false),
+        }
+    }
+
     // These 2 aren't necessarily efficient
     fn at(&self, point: &Point) -> Result<PieceIter, &str>;
     fn at_reset(&mut self, point: &Point) -> Result<PieceIter, &str>;
@@ -171,6 +202,59 @@ pub trait Board {
     }
 }
 
+fn road_cost<T: Board + ?Sized>(board: &T, point: &Point, player: Player) -> Option<usize> {
+    match board.at(point).ok().and_then(|square| square.last()) {
+        None => Some(1),
+        Some(piece) if piece.owner() == player => {
+            if piece.stone() == Stone::Standing { Some(1) } else { Some(0) }
+        }
+        Some(piece) => if piece.stone() == Stone::Flat { Some(1) } else { None },
+    }
+}
+
+// 0-1 breadth first search from one edge to the other
+fn road_distance_along<T: Board + ?Sized>(board: &T, player: Player, horizontal: bool)
+                                          -> Option<usize> {
+    let size = board.size();
+    let index = |p: &Point| p.x * size + p.y;
+    let mut best = vec![usize::max_value(); size * size];
+    let mut queue = VecDeque::new();
+
+    for i in 0..size {
+        let start = if horizontal { Point::new(0, i) } else { Point::new(i, 0) };
+        if let Some(cost) = road_cost(board, &start, player) {
+            best[index(&start)] = cost;
+            if cost == 0 {
+                queue.push_front((start, cost));
+            } else {
+                queue.push_back((start, cost));
+            }
+        }
+    }
+
+    while let Some((point, distance)) = queue.pop_front() {
+        if distance > best[index(&point)] {
+            continue;
+        }
+        if (horizontal && point.x == size - 1) || (!horizontal && point.y == size - 1) {
+            return Some(distance);
+        }
+        for next in Direction::neighbors(&point, size) {
+            if let Some(cost) = road_cost(board, &next, player) {
+                if distance + cost < best[index(&next)] {
+                    best[index(&next)] = distance + cost;
+                    if cost == 0 {
+                        queue.push_front((next, distance));
+                    } else {
+                        queue.push_back((next, distance + cost));
+                    }
+                }
+            }
+        }
+    }
+    None
+}
+
 fn parse_square<T: Board>(s: &str, b: &mut T, point: &Point)
                           -> Result<(), String> {
     let mut i = 0;
@@ -212,7 +296,6 @@ fn parse_row<T: Board>(s: &str, b: &mut T, y: usize)
     fn slice(s: &str, start: usize, end: usize) -> String {
         s.chars().skip(start).take(end - start).collect::<String>()
     }
-    println!("{}", s);
     let mut index = 0;
     for str in s.split(",") {
         if slice(str, 0, 1) == "x" {
@@ -221,24 +304,82 @@ fn parse_row<T: Board>(s: &str, b: &mut T, y: usize)
                 Err(_) => index += 1,
             }
         } else if slice(str, 0, 1) == "1" || slice(str, 0, 1) == "2" {
+            if index >= b.size() {
+                return Err("Too many squares in row".into())
+            }
             try!(parse_square(str, b, &Point::new(index, y)));
+            index += 1;
         } else {
             return Err("Empty cell should be marked with 'x'".into())
         };
     }
+    if index != b.size() {
+        return Err("Wrong number of squares in row".into())
+    }
     Ok(())
 }
 
+/// Encodes a stack from bottom to top as in TPS: the owner of each piece,
+/// with `S` or `C` after a wall or capstone on top. Empty squares are empty.
+pub fn stack_code(pieces: PieceIter) -> String {
+    let mut code = String::new();
+    let mut top = None;
+    for piece in pieces {
+        code.push(if piece.owner() == Player::One { '1' } else { '2' });
+        top = Some(piece.stone());
+    }
+    match top {
+        Some(Stone::Standing) => code.push('S'),
+        Some(Stone::Capstone) => code.push('C'),
+        _ => {}
+    }
+    code
+}
+
+/// Writes the board part of TPS, the reverse of `board_from_str`
+pub fn tps_from_board<T: Board>(b: &T) -> String {
+    let size = b.size();
+    let rows = (0..size).rev().map(|y| {
+        let mut squares = vec![];
+        let mut empty = 0;
+        for x in 0..size {
+            let code = stack_code(b.at(&Point::new(x, y)).unwrap());
+            if code.is_empty() {
+                empty += 1;
+                continue;
+            }
+            if empty > 0 {
+                squares.push(empty_code(empty));
+                empty = 0;
+            }
+            squares.push(code);
+        }
+        if empty > 0 {
+            squares.push(empty_code(empty));
+        }
+        squares.join(",")
+    }).collect::<Vec<_>>();
+    rows.join("/")
+}
+
+fn empty_code(count: usize) -> String {
+    if count == 1 { "x".into() } else { format!("x{}", count) }
+}
+
+/// Parses the board part of TPS, rows from the top down separated by `/`
+///
+/// The size is the number of rows, and every row must have that many
+/// squares.
 pub fn board_from_str<T: Board>(s: &str) -> Result<T, ()> {
     let size = s.chars().filter(|c| *c == '/').count() + 1;
-    if size != 5 {
+    if size < 3 || size > 8 {
         return Err(());
     }
     let mut board = T::new(size);
 
     let iter = s.split("/");
     for (i, str) in iter.enumerate() {
-        match parse_row(&str, &mut board, 4 - i) {
+        match parse_row(&str, &mut board, size - 1 - i) {
             Ok(x) => x,
             Err(_) => return Err(()),
         };
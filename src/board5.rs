@@ -236,6 +236,9 @@ impl Board for Board5 {
 impl FromStr for Board5 {
     type Err=();
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.split("/").count() != 5 {
+            return Err(());
+        }
         board_from_str::<Board5>(s)
     }
 }
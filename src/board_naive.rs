@@ -87,7 +87,7 @@ impl NaiveBoard {
 
 impl Board for NaiveBoard {
     fn new(board_size: usize) -> NaiveBoard {
-        assert!(board_size >= 4 && board_size <= 8);
+        assert!(board_size >= 3 && board_size <= 8);
         NaiveBoard {
             grid: vec![vec![Square::new(); board_size]; board_size],
             count: PieceCount::new(board_size),
@@ -162,7 +162,23 @@ impl Board for NaiveBoard {
 impl FromStr for NaiveBoard {
     type Err=();
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        board_from_str::<NaiveBoard>(s)
+        let mut board = try!(board_from_str::<NaiveBoard>(s));
+        // Only placements are counted while building the board, so count
+        // the stacked pieces too
+        let mut count = PieceCount::new(board.size());
+        for row in board.grid.iter() {
+            for square in row.iter() {
+                for piece in square.pieces.iter() {
+                    count.add(piece);
+                }
+            }
+        }
+        if count.p1_flat > count.max_flat || count.p1_cap > count.max_cap ||
+           count.p2_flat > count.max_flat || count.p2_cap > count.max_cap {
+            return Err(());
+        }
+        board.count = count;
+        Ok(board)
     }
 }
 
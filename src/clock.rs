@@ -0,0 +1,239 @@
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+
+use game::Game;
+use piece::Player;
+use turn::Turn;
+
+/// Where clocks get the time from, as the time since some fixed start
+pub trait TimeSource {
+    fn now(&self) -> Duration;
+}
+
+/// The real time, since the source was made
+#[derive(Clone, Copy, Debug)]
+pub struct SystemTime {
+    start: Instant,
+}
+
+impl SystemTime {
+    pub fn new() -> SystemTime {
+        SystemTime { start: Instant::now() }
+    }
+}
+
+impl TimeSource for SystemTime {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+/// Time that only moves when told to, for tests and replays
+///
+/// Clones share the same time, so a test can keep one and advance it while
+/// a clock holds another.
+#[derive(Clone, Debug, Default)]
+pub struct ManualTime {
+    now: Arc<Mutex<Duration>>,
+}
+
+impl ManualTime {
+    pub fn new() -> ManualTime {
+        ManualTime::default()
+    }
+
+    pub fn advance(&self, by: Duration) {
+        let mut now = self.now.lock().unwrap();
+        *now += by;
+    }
+}
+
+impl TimeSource for ManualTime {
+    fn now(&self) -> Duration {
+        *self.now.lock().unwrap()
+    }
+}
+
+/// How much time each player gets
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TimeControl {
+    /// Time on each clock at the start
+    pub base: Duration,
+    /// Added to a player's clock after each of their moves
+    pub increment: Duration,
+    /// How long each move can take before the clock starts counting down
+    pub delay: Duration,
+}
+
+impl TimeControl {
+    pub fn new(base: Duration, increment: Duration) -> TimeControl {
+        TimeControl {
+            base: base,
+            increment: increment,
+            delay: Duration::from_secs(0),
+        }
+    }
+
+    pub fn with_delay(mut self, delay: Duration) -> TimeControl {
+        self.delay = delay;
+        self
+    }
+}
+
+fn index(player: Player) -> usize {
+    match player {
+        Player::One => 0,
+        Player::Two => 1,
+    }
+}
+
+/// A chess clock for both players, which runs for one of them at a time
+#[derive(Clone, Debug)]
+pub struct Clock<T: TimeSource> {
+    control: TimeControl,
+    source: T,
+    remaining: [Duration; 2],
+    running: Player,
+    since: Duration,
+    used: Vec<Duration>,
+}
+
+impl<T: TimeSource> Clock<T> {
+    /// A clock running for player from now
+    pub fn new(control: TimeControl, source: T, player: Player) -> Clock<T> {
+        let now = source.now();
+        Clock {
+            control: control,
+            source: source,
+            remaining: [control.base; 2],
+            running: player,
+            since: now,
+            used: vec![],
+        }
+    }
+
+    /// The player whose clock is running
+    pub fn running(&self) -> Player {
+        self.running
+    }
+
+    fn elapsed(&self) -> Duration {
+        self.source.now() - self.since
+    }
+
+    /// Time left for player, as of now
+    pub fn remaining(&self, player: Player) -> Duration {
+        let left = self.remaining[index(player)];
+        if player != self.running {
+            return left;
+        }
+        let charged = self.elapsed().checked_sub(self.control.delay).unwrap_or(Duration::from_secs(0));
+        left.checked_sub(charged).unwrap_or(Duration::from_secs(0))
+    }
+
+    /// The running player, if their time has run out
+    pub fn flagged(&self) -> Option<Player> {
+        if self.remaining(self.running) == Duration::from_secs(0) {
+            Some(self.running)
+        } else {
+            None
+        }
+    }
+
+    /// Ends the running player's move: charges their clock, adds the
+    /// increment and starts the other clock. Returns how long the move
+    /// took.
+    pub fn press(&mut self) -> Duration {
+        let used = self.elapsed();
+        let player = self.running;
+        self.remaining[index(player)] = self.remaining(player) + self.control.increment;
+        self.running = player.other();
+        self.since = self.source.now();
+        self.used.push(used);
+        used
+    }
+
+    /// How long each move took, in order
+    pub fn used(&self) -> &[Duration] {
+        &self.used
+    }
+}
+
+/// A game played on a clock, which switches after every accepted move
+///
+/// A player whose time runs out loses, as soon as anyone checks: on their
+/// next move or a call to `check_time`.
+#[derive(Clone, Debug)]
+pub struct TimedGame<T: TimeSource> {
+    game: Game,
+    clock: Clock<T>,
+    forfeit: Option<Player>,
+}
+
+impl<T: TimeSource> TimedGame<T> {
+    /// Starts the clock of the player to move in game
+    pub fn new(game: Game, control: TimeControl, source: T) -> TimedGame<T> {
+        let player = game.next_player();
+        TimedGame {
+            game: game,
+            clock: Clock::new(control, source, player),
+            forfeit: None,
+        }
+    }
+
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    pub fn clock(&self) -> &Clock<T> {
+        &self.clock
+    }
+
+    /// Plays a PTN move, returning the winner if it ended the game
+    pub fn play(&mut self, turn: &str) -> Result<Option<Player>, String> {
+        match turn.parse::<Turn>() {
+            Ok(turn) => self.play_turn(turn),
+            Err(_) => Err("Invalid move".into()),
+        }
+    }
+
+    pub fn play_turn(&mut self, turn: Turn) -> Result<Option<Player>, String> {
+        if self.forfeit.is_some() || self.game.check_winner().is_some() {
+            return Err("The game is over".into());
+        }
+        if let Some(player) = self.check_time() {
+            let number = if player == Player::One { 1 } else { 2 };
+            return Err(format!("Player {} ran out of time", number));
+        }
+        let winner = try!(self.game.play_turn(turn));
+        self.clock.press();
+        Ok(winner)
+    }
+
+    /// The player who ran out of time, if one has
+    pub fn check_time(&mut self) -> Option<Player> {
+        if self.forfeit.is_none() && self.game.check_winner().is_none() {
+            self.forfeit = self.clock.flagged();
+        }
+        self.forfeit
+    }
+
+    /// The PTN result, counting a loss on time as `0-1` or `1-0`
+    pub fn result(&self) -> &'static str {
+        match self.forfeit {
+            Some(Player::One) => "0-1",
+            Some(Player::Two) => "1-0",
+            None => self.game.result(),
+        }
+    }
+
+    /// PTN with the time each move took as a comment, such as `{1.250s}`
+    pub fn as_ptn_with_players(&self, player1: &str, player2: &str) -> String {
+        let comments = self.clock.used().iter().map(|used| {
+            format!("{}.{:03}s", used.as_secs(), used.subsec_nanos() / 1_000_000)
+        }).collect::<Vec<_>>();
+        self.game.as_ptn_with_comments(player1, player2, self.result(), &comments)
+    }
+}
@@ -0,0 +1,185 @@
+//! A C interface to games and the AI, declared in `include/tak.h`
+//!
+//! Games are opaque handles made by `tak_game_new` or `tak_game_from_tps`
+//! and released with `tak_game_free`. Strings returned to C are owned by
+//! the caller and released with `tak_string_free`. Functions that fail
+//! return -1 or NULL, and `tak_last_error` says why.
+
+use std::cell::RefCell;
+use std::ffi::CStr;
+use std::ffi::CString;
+use std::os::raw::c_char;
+use std::os::raw::c_int;
+use std::ptr;
+
+use ai::Ai;
+use game::Game;
+use piece::Player;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = RefCell::new(None);
+}
+
+fn set_error(e: String) {
+    let e = CString::new(e).unwrap_or_else(|_| CString::new("Error contains a NUL byte").unwrap());
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(e));
+}
+
+fn to_c(s: String) -> *mut c_char {
+    // PTN, TPS and results never contain NUL bytes
+    CString::new(s).unwrap().into_raw()
+}
+
+unsafe fn from_c<'a>(s: *const c_char) -> Result<&'a str, String> {
+    if s.is_null() {
+        return Err("String is NULL".into());
+    }
+    CStr::from_ptr(s).to_str().map_err(|_| "String isn't UTF-8".to_string())
+}
+
+/// A new game on an empty board, or NULL if the size isn't 3 to 8
+#[no_mangle]
+pub extern "C" fn tak_game_new(size: usize) -> *mut Game {
+    if size < 3 || size > 8 {
+        set_error(format!("Unsupported board size {}", size));
+        return ptr::null_mut();
+    }
+    Box::into_raw(Box::new(Game::new(size)))
+}
+
+/// A game starting from a TPS position, or NULL if it doesn't parse
+#[no_mangle]
+pub unsafe extern "C" fn tak_game_from_tps(tps: *const c_char) -> *mut Game {
+    match from_c(tps).and_then(|tps| Game::from_tps(tps)) {
+        Ok(game) => Box::into_raw(Box::new(game)),
+        Err(e) => {
+            set_error(e);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Releases a game. NULL is ignored.
+#[no_mangle]
+pub unsafe extern "C" fn tak_game_free(game: *mut Game) {
+    if !game.is_null() {
+        drop(Box::from_raw(game));
+    }
+}
+
+/// Plays a PTN move for the player to move, returning 0, or -1 if the move
+/// is invalid or illegal
+#[no_mangle]
+pub unsafe extern "C" fn tak_game_play(game: *mut Game, turn: *const c_char) -> c_int {
+    let result = match (game.as_mut(), from_c(turn)) {
+        (None, _) => Err("Game is NULL".into()),
+        (_, Err(e)) => Err(e),
+        (Some(game), Ok(turn)) => {
+            if game.check_winner().is_some() {
+                Err("The game is over".into())
+            } else {
+                game.play_simple(turn)
+            }
+        }
+    };
+    match result {
+        Ok(_) => 0,
+        Err(e) => {
+            set_error(e);
+            -1
+        }
+    }
+}
+
+/// The position in TPS
+#[no_mangle]
+pub unsafe extern "C" fn tak_game_tps(game: *const Game) -> *mut c_char {
+    match game.as_ref() {
+        Some(game) => to_c(game.to_tps()),
+        None => {
+            set_error("Game is NULL".into());
+            ptr::null_mut()
+        }
+    }
+}
+
+/// The PTN result, such as `R-0`, or an empty string while the game goes on
+#[no_mangle]
+pub unsafe extern "C" fn tak_game_result(game: *const Game) -> *mut c_char {
+    match game.as_ref() {
+        Some(game) => to_c(game.result().into()),
+        None => {
+            set_error("Game is NULL".into());
+            ptr::null_mut()
+        }
+    }
+}
+
+/// 1 or 2 for the winner, 0 while the game goes on, or -1 for NULL
+#[no_mangle]
+pub unsafe extern "C" fn tak_game_winner(game: *const Game) -> c_int {
+    match game.as_ref().map(|game| game.check_winner()) {
+        Some(Some(Player::One)) => 1,
+        Some(Some(Player::Two)) => 2,
+        Some(None) => 0,
+        None => {
+            set_error("Game is NULL".into());
+            -1
+        }
+    }
+}
+
+/// Every legal move in PTN, separated by spaces, and empty once the game
+/// is over
+#[no_mangle]
+pub unsafe extern "C" fn tak_game_legal_moves(game: *const Game) -> *mut c_char {
+    match game.as_ref() {
+        Some(game) if game.check_winner().is_some() => to_c(String::new()),
+        Some(game) => {
+            let moves = game.legal_moves().iter().map(|turn| turn.to_string()).collect::<Vec<_>>();
+            to_c(moves.join(" "))
+        }
+        None => {
+            set_error("Game is NULL".into());
+            ptr::null_mut()
+        }
+    }
+}
+
+/// The AI's move in PTN, searching depth plies, or NULL once the game is
+/// over
+#[no_mangle]
+pub unsafe extern "C" fn tak_ai_move(game: *const Game, depth: usize) -> *mut c_char {
+    match game.as_ref() {
+        Some(game) if game.check_winner().is_some() => {
+            set_error("The game is over".into());
+            ptr::null_mut()
+        }
+        Some(game) => {
+            let ai = Ai::new(game.next_player()).with_depth(depth.max(1));
+            to_c(ai.best_move(game).to_string())
+        }
+        None => {
+            set_error("Game is NULL".into());
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Releases a string returned by this library. NULL is ignored.
+#[no_mangle]
+pub unsafe extern "C" fn tak_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// Why the last failing call on this thread failed, or NULL. The string
+/// belongs to the library and lasts until the next failure.
+#[no_mangle]
+pub extern "C" fn tak_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| match *last.borrow() {
+        Some(ref e) => e.as_ptr(),
+        None => ptr::null(),
+    })
+}
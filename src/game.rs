@@ -1,13 +1,22 @@
 extern crate rustc_serialize;
+use std::cmp;
 use std::collections::VecDeque;
 use std::fmt;
+use std::mem;
 
-use ai::Ai;
+use ai::moves::MoveIter;
+use ai::moves::Phase;
+use ai::moves::OPENING;
+use ai::moves::PLACEMENTS_FIRST;
+use ai::strategy::Strategy;
 use turn::Turn;
 use turn::Direction;
 use board::Board;
+use board::tps_from_board;
 use board5::Board5;
 use board_naive::NaiveBoard;
+use observer::Event;
+use observer::Observers;
 use piece::Player;
 use piece::Stone;
 use piece::Piece;
@@ -18,6 +27,10 @@ pub struct Game {
     board: NaiveBoard,
     next: Player,
     history: Vec<Turn>,
+    /// TPS of the first position, for games that didn't start empty
+    start: Option<String>,
+    first_ply: usize,
+    observers: Observers,
 }
 
 impl fmt::Display for Game {
@@ -38,20 +51,112 @@ impl Game {
             board: NaiveBoard::new(size),
             next: Player::One,
             history: vec![],
+            start: None,
+            first_ply: 0,
+            observers: Observers::default(),
         }
     }
 
+    /// Sets up a game from Tak Positional System notation, such as
+    /// `x5/x5/x2,1,x2/x5/x5 2 1`: the rows from the top down, the player
+    /// to move and the move number
+    pub fn from_tps(tps: &str) -> Result<Game, String> {
+        let parts = tps.split_whitespace().collect::<Vec<_>>();
+        if parts.len() != 3 {
+            return Err("TPS needs a board, the player to move and the move number".into());
+        }
+        let board = try!(parts[0].parse::<NaiveBoard>().map_err(|_| format!("Invalid TPS board: {}", parts[0])));
+        let next = match parts[1] {
+            "1" => Player::One,
+            "2" => Player::Two,
+            _ => return Err(format!("Invalid player to move: {}", parts[1])),
+        };
+        let number = match parts[2].parse::<usize>() {
+            Ok(number) if number >= 1 => number,
+            _ => return Err(format!("Invalid move number: {}", parts[2])),
+        };
+        let mut game = Game {
+            board: board,
+            next: next,
+            history: vec![],
+            start: None,
+            first_ply: (number - 1) * 2 + if next == Player::Two { 1 } else { 0 },
+            observers: Observers::default(),
+        };
+        game.start = Some(game.to_tps());
+        Ok(game)
+    }
+
+    /// The current position in Tak Positional System notation
+    pub fn to_tps(&self) -> String {
+        let player = if self.next == Player::One { 1 } else { 2 };
+        format!("{} {} {}", tps_from_board(&self.board), player, self.turn_number() / 2 + 1)
+    }
+
+    /// How many turns have been played, including those before a TPS start
     pub fn turn_number(&self) -> usize {
-        self.history.len()
+        self.first_ply + self.history.len()
+    }
+
+    /// Asks a strategy, such as an `Ai`, for the move to play next
+    pub fn predict<S: Strategy>(&self, mut strategy: S) -> Turn {
+        strategy.choose(self)
     }
 
-    pub fn predict(&self, ai: Ai) -> Turn {
-        ai.next_move(self.turn_number(), &self.board)
+    pub fn next_player(&self) -> Player {
+        self.next
     }
 
-    fn check_winner(&self) -> Option<Player> {
+    pub fn board(&self) -> &NaiveBoard {
+        &self.board
+    }
+
+    pub fn check_winner(&self) -> Option<Player> {
         self.check_road_winner().or(self.check_flat_winner())
     }
+
+    /// Hashes the board, the player to move, and whether the opening swap
+    /// is still in effect
+    ///
+    /// Uses FNV-1a over the stacks rather than the std hasher, so that the
+    /// value is stable between runs and builds.
+    pub fn position_hash(&self) -> u64 {
+        let mut hash: u64 = 0xcbf29ce484222325;
+        {
+            let mut feed = |byte: u8| {
+                hash = (hash ^ byte as u64).wrapping_mul(0x100000001b3);
+            };
+            feed(self.size() as u8);
+            feed(self.next as u8);
+            feed(cmp::min(self.turn_number(), 2) as u8);
+            for square in self.board.squares() {
+                for piece in square {
+                    feed(piece as u8);
+                }
+                feed(0);
+            }
+        }
+        hash
+    }
+
+    /// Lists the moves available to the player whose turn it is
+    ///
+    /// During the first two turns only flat placements (of the opponent's
+    /// stone) are allowed.
+    pub fn legal_moves(&self) -> Vec<Turn> {
+        self.moves(PLACEMENTS_FIRST).collect()
+    }
+
+    /// Lazily generates the moves available to the player whose turn it is,
+    /// in the given phase order
+    pub fn moves<'a>(&'a self, order: &'a [Phase]) -> MoveIter<'a, NaiveBoard> {
+        if self.turn_number() < 2 {
+            MoveIter::new(&self.board, self.next, OPENING)
+        } else {
+            MoveIter::new(&self.board, self.next, order)
+        }
+    }
+
     pub fn size(&self) -> usize {
         self.board.size()
     }
@@ -61,16 +166,12 @@ impl Game {
     }
 
     pub fn play_simple(&mut self, turn: &str) -> Result<Option<Player>, String> {
-        let player = if self.history.len() % 2 == 0 {
-            Player::One
-        } else {
-            Player::Two
-        };
+        let player = self.next;
         self.player_move(turn, player)
     }
 
     pub fn player_move(&mut self, turn: &str, player: Player) -> Result<Option<Player>, String> {
-        let owner = if self.history.len() >= 2 {
+        let owner = if self.turn_number() >= 2 {
             Some(player)
         } else {
             Some(player.other())
@@ -88,6 +189,18 @@ impl Game {
         }
     }
 
+    /// Plays a turn for the player whose turn it is
+    ///
+    /// Placements use the opponent's stone during the first two turns.
+    pub fn play_turn(&mut self, turn: Turn) -> Result<Option<Player>, String> {
+        let owner = if self.turn_number() >= 2 {
+            Some(self.next)
+        } else {
+            Some(self.next.other())
+        };
+        self.play_parsed(turn, owner)
+    }
+
     pub fn play_parsed(&mut self, turn: Turn, owner: Option<Player>) -> Result<Option<Player>, String> {
         match turn {
             Turn::Place { ref point, ref stone } => {
@@ -97,12 +210,44 @@ impl Game {
                 }
             }
             Turn::Slide { ref num_pieces, ref point, ref direction, ref drops } => {
-                try!(self.slide(num_pieces, point, direction, drops));
+                // A slide can fail after picking up the stack, so put the
+                // board back if it does
+                let before = self.board.clone();
+                if let Err(e) = self.slide(num_pieces, point, direction, drops) {
+                    self.board = before;
+                    return Err(e);
+                }
             }
         }
         self.history.push(turn);
         self.next = self.next.other();
-        Ok(self.check_winner())
+        let winner = self.check_winner();
+        if !self.observers.is_empty() {
+            let turn = self.history[self.history.len() - 1].clone();
+            self.notify(Event::Moved(turn));
+            if winner.is_some() {
+                let result = self.result().to_string();
+                self.notify(Event::Over(result));
+            } else if self.is_tak() {
+                let player = self.next.other();
+                self.notify(Event::Tak(player));
+            }
+        }
+        Ok(winner)
+    }
+
+    /// Calls observer after every move, Tak, game end and undo
+    ///
+    /// Observers only see this game: clones start without any.
+    pub fn observe<F: FnMut(&Event, &Game) + Send + 'static>(&mut self, observer: F) {
+        self.observers.push(Box::new(observer));
+    }
+
+    fn notify(&mut self, event: Event) {
+        // Observers get to look at the game, so take them out of it first
+        let mut observers = mem::replace(&mut self.observers, Observers::default());
+        observers.notify(&event, self);
+        self.observers = observers;
     }
 
     fn place(&mut self, point: &Point, stone: &Stone, owner: &Player) -> Result<(), String> {
@@ -163,12 +308,66 @@ impl Game {
         Ok(())
     }
 
+    /// The PTN result of the game, or an empty string if it isn't over
+    pub fn result(&self) -> &'static str {
+        match (self.check_road_winner(), self.check_flat_winner()) {
+            (Some(Player::One), _) => "R-0",
+            (Some(Player::Two), _) => "0-R",
+            (None, Some(Player::One)) => "F-0",
+            (None, Some(Player::Two)) => "0-F",
+            (None, None) => "",
+        }
+    }
+
     pub fn as_ptn(&self) -> String {
-        let mut response: String = "[Date \"2016.09.16\"]\n[Player1 \"anon1\"]\n\
-                                    [Player2 \"anon2\"]\n[Result \"\"]\n".into();
+        self.as_ptn_with_players("anon1", "anon2")
+    }
+
+    pub fn as_ptn_with_players(&self, player1: &str, player2: &str) -> String {
+        self.as_ptn_with_result(player1, player2, self.result())
+    }
+
+    /// PTN with a result that didn't come from the board, such as `0-1`
+    /// after player one resigns
+    pub fn as_ptn_with_result(&self, player1: &str, player2: &str, result: &str) -> String {
+        self.as_ptn_with_comments(player1, player2, result, &[])
+    }
+
+    /// PTN with a `{comment}` after each move, such as the time it took.
+    /// Moves past the end of comments get none.
+    pub fn as_ptn_with_comments(&self, player1: &str, player2: &str, result: &str,
+                                comments: &[String]) -> String {
+        let mut response = format!("[Date \"2016.09.16\"]\n[Player1 \"{}\"]\n\
+                                    [Player2 \"{}\"]\n[Result \"{}\"]\n",
+                                   player1, player2, result);
         response.push_str(&(format!("[Size \"{}\"]\n", self.board.size())));
-        let mut turns = self.history.iter();
-        let mut count = 1;
+        if let Some(ref start) = self.start {
+            response.push_str(&(format!("[TPS \"{}\"]\n", start)));
+        }
+        // Replay the game to mark the moves that threaten a road
+        let mut replay = self.start_position();
+        let annotated = self.history.iter().enumerate().map(|(i, turn)| {
+            // Only moves that were played are in the history, so they
+            // always replay
+            replay.play_turn(turn.clone()).expect("history should replay");
+            let mut written = if replay.check_winner().is_none() && replay.is_tak() {
+                format!("{}'", turn)
+            } else {
+                turn.to_string()
+            };
+            if let Some(comment) = comments.get(i) {
+                written.push_str(&format!(" {{{}}}", comment));
+            }
+            written
+        }).collect::<Vec<_>>();
+        let mut turns = annotated.iter();
+        let mut count = self.first_ply / 2 + 1;
+        if self.first_ply % 2 == 1 {
+            if let Some(p2_turn) = turns.next() {
+                response.push_str(&(format!("{}. -- {}\n", count, p2_turn)));
+                count += 1;
+            }
+        }
         while let Some(p1_turn) = turns.next() {
             response.push_str(&(format!("{}. {}", count, p1_turn)));
             if let Some(p2_turn) = turns.next() {
@@ -179,43 +378,82 @@ impl Game {
         response
     }
 
-    /// Checks for the winner via a road win
+    /// Every turn played so far, oldest first
+    pub fn history(&self) -> &[Turn] {
+        &self.history
+    }
+
+    /// Takes back the last turn, returning it
+    pub fn undo(&mut self) -> Option<Turn> {
+        let last = match self.history.pop() {
+            Some(turn) => turn,
+            None => return None,
+        };
+        let mut replay = self.start_position();
+        for turn in self.history.iter() {
+            replay.play_turn(turn.clone()).expect("history should replay");
+        }
+        replay.observers = mem::replace(&mut self.observers, Observers::default());
+        *self = replay;
+        self.notify(Event::Undone(last.clone()));
+        Some(last)
+    }
+
+    /// The game as it was before any of its moves were played
+    fn start_position(&self) -> Game {
+        match self.start {
+            Some(ref tps) => Game::from_tps(tps).expect("start position should parse"),
+            None => Game::new(self.size()),
+        }
+    }
+
+    /// Lists every move that would immediately complete a road for player,
+    /// as if it were their turn
+    pub fn road_threats(&self, player: Player) -> Vec<Turn> {
+        if self.turn_number() < 2 || self.check_winner().is_some() {
+            return vec![];
+        }
+        let mut game = self.clone();
+        game.next = player;
+        game.legal_moves().into_iter().filter(|turn| {
+            let mut next = game.clone();
+            next.play_turn(turn.clone()).is_ok() && next.has_road(player)
+        }).collect()
+    }
+
+    /// Whether the player who just moved threatens to win with a road on
+    /// their next move (and so should call "Tak")
+    pub fn is_tak(&self) -> bool {
+        !self.road_threats(self.next.other()).is_empty()
+    }
+
+    /// Checks whether player has a road, in either direction
     ///
-    /// Uses follow to go from the right wall as far left as possible for each
-    /// player, and then uses follow to go from the bottom wall as far up as
-    /// possible. If the string of connected pieces reaches the far wall, it's
-    /// a win.
+    /// Uses follow to go from the left wall as far right as possible, and
+    /// then from the bottom wall as far up as possible. If the string of
+    /// connected pieces reaches the far wall, it's a road.
+    pub fn has_road(&self, player: Player) -> bool {
+        let size = self.size();
+        let mut points = (0..size).map(|y| Point { x: 0, y: y }).collect::<VecDeque<_>>();
+        if self.board.follow(&mut points, player).iter().any(|p| p.x == size - 1) {
+            return true;
+        }
+        let mut points = (0..size).map(|x| Point { x: x, y: 0 }).collect::<VecDeque<_>>();
+        self.board.follow(&mut points, player).iter().any(|p| p.y == size - 1)
+    }
+
+    /// Checks for the winner via a road win
     ///
-    /// Returns when the first winner is found. It will give a weird (wrong?)
-    /// answer when a move causes both players to "win". Is there a rule about
-    /// that?
-    fn check_road_winner(&self) -> Option<Player> {
-        let mut points = (0..self.size()).map(|y| Point { x: 0, y: y })
-                                         .collect::<VecDeque<_>>();
-        if self.board.follow(&mut points.clone(), Player::One)
-               .iter()
-               .any(|p| p.x == self.size() - 1) {
-            return Some(Player::One);
-        }
-        if self.board.follow(&mut points, Player::Two)
-               .iter()
-               .any(|p| p.x == self.size() - 1) {
-            return Some(Player::Two);
-        }
-
-        let mut points = (0..self.size()).map(|x| Point { x: x, y: 0 })
-                                         .collect::<VecDeque<_>>();
-        if self.board.follow(&mut points.clone(), Player::One)
-               .iter()
-               .any(|p| p.y == self.size() - 1) {
-            return Some(Player::One);
-        }
-        if self.board.follow(&mut points, Player::Two)
-               .iter()
-               .any(|p| p.y == self.size() - 1) {
-            return Some(Player::Two);
+    /// If a slide completes roads for both players at once, player one is
+    /// returned. Is there a rule about that?
+    pub fn check_road_winner(&self) -> Option<Player> {
+        if self.has_road(Player::One) {
+            Some(Player::One)
+        } else if self.has_road(Player::Two) {
+            Some(Player::Two)
+        } else {
+            None
         }
-        None
     }
 
     /// Checks for the winner via a flat win
@@ -225,8 +463,8 @@ impl Game {
     pub fn check_flat_winner(&self) -> Option<Player> {
         let used = (self.board.count().used_up(&Piece::new(Stone::Flat, Player::One)) &&
                     self.board.count().used_up(&Piece::new(Stone::Capstone, Player::One))) ||
-                   (self.board.count().used_up(&Piece::new(Stone::Flat, Player::One)) &&
-                    self.board.count().used_up(&Piece::new(Stone::Capstone, Player::One)));
+                   (self.board.count().used_up(&Piece::new(Stone::Flat, Player::Two)) &&
+                    self.board.count().used_up(&Piece::new(Stone::Capstone, Player::Two)));
 
         if used || self.board.full() {
             let mut p1_top = 0;
@@ -2,6 +2,14 @@
 extern crate enum_primitive;
 extern crate rustc_serialize;
 extern crate twiddle;
+#[cfg(feature = "wasm")]
+extern crate wasm_bindgen;
+#[cfg(feature = "python")]
+extern crate pyo3;
+// Code generated by pyo3 names `::core`, which only resolves in this edition
+// with the crate declared at the root
+#[cfg(feature = "python")]
+extern crate core;
 
 pub mod piece;
 pub mod point;
@@ -9,17 +17,32 @@ pub mod turn;
 pub mod board;
 pub mod board5;
 pub mod board_naive;
+pub mod clock;
+pub mod ffi;
 pub mod game;
+pub mod manager;
+pub mod observer;
+pub mod playtak;
+pub mod ptn;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod tei;
 pub mod ai;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
 pub use ai::Ai;
+pub use ai::Level;
+pub use ai::strategy::Strategy;
 pub use turn::Turn;
 pub use turn::Direction;
 pub use game::Game;
 pub use board::Board;
+pub use board::RoadDistance;
 pub use board_naive::NaiveBoard;
 pub use board5::Board5;
 pub use piece::Player;
 pub use piece::Stone;
 pub use piece::Piece;
 pub use point::Point;
+pub use ptn::Ptn;
@@ -0,0 +1,175 @@
+extern crate tak;
+
+use std::env;
+use std::fs::File;
+use std::io;
+use std::io::BufRead;
+use std::io::BufWriter;
+use std::io::Read;
+use std::io::Write;
+use std::process;
+
+use tak::Ai;
+use tak::Game;
+use tak::Player;
+use tak::ai::eval::Weights;
+use tak::ai::export;
+use tak::ai::tune;
+use tak::ptn;
+
+const USAGE: &'static str = "Usage:
+    tak tune <ptn directory> <weights file> [passes]
+    tak export <ptn directory> <size> <csv file>
+    tak play [--size <size>] [--vs ai|human] [--save <ptn file>]
+    tak check <ptn file>";
+
+fn main() {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    let result = match args.first().map(|s| s.as_str()) {
+        Some("tune") => run_tune(&args[1..]),
+        Some("export") => run_export(&args[1..]),
+        Some("play") => run_play(&args[1..]),
+        Some("check") => run_check(&args[1..]),
+        _ => Err(USAGE.into()),
+    };
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+}
+
+/// Tunes the evaluation weights on a directory of PTN games, starting from
+/// the weights file if it already exists
+fn run_tune(args: &[String]) -> Result<(), String> {
+    if args.len() < 2 || args.len() > 3 {
+        return Err(USAGE.into());
+    }
+    let passes = match args.get(2) {
+        Some(s) => try!(s.parse::<usize>().map_err(|_| format!("Invalid passes: {}", s))),
+        None => 100,
+    };
+    let samples = try!(tune::samples_from_ptn_dir(&args[0]).map_err(|e| e.to_string()));
+    if samples.is_empty() {
+        return Err(format!("No finished games found in {}", args[0]));
+    }
+    let start = match Weights::load(&args[1]) {
+        Ok(weights) => weights,
+        Err(ref e) if e.kind() == io::ErrorKind::NotFound => Weights::default(),
+        Err(e) => return Err(format!("Couldn't read {}: {}", args[1], e)),
+    };
+    println!("{} positions, error {:.6}", samples.len(), tune::error(&samples, &start));
+
+    let tuned = tune::tune(&samples, start, passes);
+    println!("Tuned error {:.6}", tune::error(&samples, &tuned));
+    try!(tuned.save(&args[1]).map_err(|e| e.to_string()));
+    println!("Saved weights to {}", args[1]);
+    Ok(())
+}
+
+/// Writes every position of the finished games of one size as CSV
+fn run_export(args: &[String]) -> Result<(), String> {
+    if args.len() != 3 {
+        return Err(USAGE.into());
+    }
+    let size = try!(args[1].parse::<usize>().map_err(|_| format!("Invalid size: {}", args[1])));
+    let file = try!(File::create(&args[2]).map_err(|e| e.to_string()));
+    let mut out = BufWriter::new(file);
+    let rows = try!(export::export_ptn_dir(&args[0], size, &mut out).map_err(|e| e.to_string()));
+    println!("Wrote {} positions to {}", rows, args[2]);
+    Ok(())
+}
+
+/// Plays a game at the terminal, against the AI or another person at the
+/// same keyboard, and saves it as PTN once it's over
+fn run_play(args: &[String]) -> Result<(), String> {
+    let mut size = 5;
+    let mut vs_ai = true;
+    let mut save = "game.ptn".to_string();
+    let mut args = args.iter();
+    while let Some(flag) = args.next() {
+        let value = try!(args.next().ok_or(USAGE.to_string()));
+        match flag.as_str() {
+            "--size" => {
+                size = match value.parse::<usize>() {
+                    Ok(size) if size >= 3 && size <= 8 => size,
+                    _ => return Err(format!("Invalid size: {}", value)),
+                }
+            }
+            "--vs" if value == "ai" => vs_ai = true,
+            "--vs" if value == "human" => vs_ai = false,
+            "--save" => save = value.clone(),
+            _ => return Err(USAGE.into()),
+        }
+    }
+
+    let ai = Ai::new(Player::Two);
+    let mut game = Game::new(size);
+    let stdin = io::stdin();
+    let mut input = stdin.lock().lines();
+    let mut resigned = None;
+    println!("Enter moves in PTN, or undo or resign.");
+    while game.check_winner().is_none() {
+        println!("{}", game);
+        if vs_ai && game.next_player() == Player::Two {
+            let turn = ai.best_move(&game);
+            println!("AI plays {}", turn);
+            try!(game.play_turn(turn));
+            continue;
+        }
+
+        let player = if game.next_player() == Player::One { 1 } else { 2 };
+        print!("Player {}> ", player);
+        try!(io::stdout().flush().map_err(|e| e.to_string()));
+        let line = match input.next() {
+            Some(line) => try!(line.map_err(|e| e.to_string())),
+            None => return Err("Quit without finishing the game".into()),
+        };
+        match line.trim() {
+            "" => {}
+            "undo" => {
+                // Against the AI, take back its reply as well
+                let plies = if vs_ai && game.turn_number() % 2 == 0 { 2 } else { 1 };
+                for _ in 0..plies {
+                    game.undo();
+                }
+            }
+            "resign" => {
+                resigned = Some(game.next_player());
+                break;
+            }
+            turn => {
+                if let Err(e) = game.play_simple(turn) {
+                    println!("{}", e);
+                }
+            }
+        }
+    }
+
+    println!("{}", game);
+    let result = match resigned {
+        Some(Player::One) => "0-1",
+        Some(Player::Two) => "1-0",
+        None => game.result(),
+    };
+    println!("Result: {}", result);
+    let (player1, player2) = if vs_ai { ("you", "tak-rs") } else { ("player1", "player2") };
+    let mut file = try!(File::create(&save).map_err(|e| format!("Couldn't write {}: {}", save, e)));
+    try!(file.write_all(game.as_ptn_with_result(player1, player2, result).as_bytes())
+             .map_err(|e| format!("Couldn't write {}: {}", save, e)));
+    println!("Saved the game to {}", save);
+    Ok(())
+}
+
+/// Referees a PTN file, reporting where the first problem is
+fn run_check(args: &[String]) -> Result<(), String> {
+    if args.len() != 1 {
+        return Err(USAGE.into());
+    }
+    let mut text = String::new();
+    try!(File::open(&args[0]).and_then(|mut file| file.read_to_string(&mut text))
+                             .map_err(|e| format!("Couldn't read {}: {}", args[0], e)));
+    let game = try!(ptn::check(&text).map_err(|problem| format!("{}: {}", args[0], problem)));
+    let result = if game.result().is_empty() { "unfinished" } else { game.result() };
+    println!("{}: {} moves, {}", args[0], game.history().len(), result);
+    Ok(())
+}
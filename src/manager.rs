@@ -0,0 +1,209 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::sync::mpsc;
+use std::sync::mpsc::Receiver;
+use std::sync::mpsc::Sender;
+
+use game::Game;
+use piece::Player;
+use turn::Turn;
+
+pub type GameId = u64;
+
+/// Something that happened in one of the hosted games
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Event {
+    Started {
+        game: GameId,
+        size: usize,
+        player_one: String,
+        player_two: String,
+    },
+    Moved {
+        game: GameId,
+        player: Player,
+        turn: Turn,
+    },
+    /// The game ended on the board or by resignation, with its PTN result
+    Over {
+        game: GameId,
+        result: String,
+    },
+}
+
+struct Hosted {
+    game: Game,
+    seats: [String; 2],
+    result: Option<String>,
+}
+
+impl Hosted {
+    fn seat(&self, identity: &str) -> Option<Player> {
+        if self.seats[0] == identity {
+            Some(Player::One)
+        } else if self.seats[1] == identity {
+            Some(Player::Two)
+        } else {
+            None
+        }
+    }
+}
+
+struct Games {
+    next_id: GameId,
+    games: HashMap<GameId, Hosted>,
+    listeners: Vec<Sender<Event>>,
+}
+
+impl Games {
+    fn emit(&mut self, event: Event) {
+        // Forget listeners that hung up
+        self.listeners.retain(|listener| listener.send(event.clone()).is_ok());
+    }
+
+    fn hosted(&mut self, id: GameId) -> Result<&mut Hosted, String> {
+        self.games.get_mut(&id).ok_or(format!("No game {}", id))
+    }
+}
+
+/// Hosts many games at once for a server, keyed by id
+///
+/// Players are identified by any string, such as a user name. Each game
+/// seats two of them, and only the player whose turn it is may move. All
+/// methods take `&self`, so one manager can be shared between connection
+/// threads in an `Arc`.
+pub struct GameManager {
+    inner: Mutex<Games>,
+}
+
+impl GameManager {
+    pub fn new() -> GameManager {
+        GameManager {
+            inner: Mutex::new(Games {
+                next_id: 1,
+                games: HashMap::new(),
+                listeners: vec![],
+            }),
+        }
+    }
+
+    /// Every event from now on, in the order they happened
+    pub fn subscribe(&self) -> Receiver<Event> {
+        let (sender, receiver) = mpsc::channel();
+        self.inner.lock().unwrap().listeners.push(sender);
+        receiver
+    }
+
+    /// Starts a game between two different players, returning its id
+    pub fn create(&self, size: usize, player_one: &str, player_two: &str) -> Result<GameId, String> {
+        if size < 3 || size > 8 {
+            return Err(format!("Unsupported board size {}", size));
+        }
+        if player_one == player_two {
+            return Err("A player can't take both seats".into());
+        }
+        let mut inner = self.inner.lock().unwrap();
+        let id = inner.next_id;
+        inner.next_id += 1;
+        inner.games.insert(id, Hosted {
+            game: Game::new(size),
+            seats: [player_one.into(), player_two.into()],
+            result: None,
+        });
+        inner.emit(Event::Started {
+            game: id,
+            size: size,
+            player_one: player_one.into(),
+            player_two: player_two.into(),
+        });
+        Ok(id)
+    }
+
+    /// Plays a PTN move for the player with this identity, returning the
+    /// winner if it ended the game
+    pub fn play(&self, id: GameId, identity: &str, turn: &str) -> Result<Option<Player>, String> {
+        let turn = try!(turn.parse::<Turn>().map_err(|_| format!("Invalid move: {}", turn)));
+        let mut inner = self.inner.lock().unwrap();
+        let (player, winner, result) = {
+            let hosted = try!(inner.hosted(id));
+            if hosted.result.is_some() {
+                return Err("The game is over".into());
+            }
+            let player = try!(hosted.seat(identity).ok_or(format!("{} isn't playing game {}", identity, id)));
+            if hosted.game.next_player() != player {
+                return Err("Not your turn".into());
+            }
+            let winner = try!(hosted.game.play_turn(turn.clone()));
+            if winner.is_some() {
+                hosted.result = Some(hosted.game.result().into());
+            }
+            (player, winner, hosted.result.clone())
+        };
+        inner.emit(Event::Moved {
+            game: id,
+            player: player,
+            turn: turn,
+        });
+        if let Some(result) = result {
+            inner.emit(Event::Over {
+                game: id,
+                result: result,
+            });
+        }
+        Ok(winner)
+    }
+
+    /// Ends the game as a loss for the player with this identity
+    pub fn resign(&self, id: GameId, identity: &str) -> Result<(), String> {
+        let mut inner = self.inner.lock().unwrap();
+        let result = {
+            let hosted = try!(inner.hosted(id));
+            if hosted.result.is_some() {
+                return Err("The game is over".into());
+            }
+            let result = match hosted.seat(identity) {
+                Some(Player::One) => "0-1",
+                Some(Player::Two) => "1-0",
+                None => return Err(format!("{} isn't playing game {}", identity, id)),
+            };
+            hosted.result = Some(result.into());
+            result
+        };
+        inner.emit(Event::Over {
+            game: id,
+            result: result.into(),
+        });
+        Ok(())
+    }
+
+    /// A copy of the game as it stands
+    pub fn game(&self, id: GameId) -> Option<Game> {
+        self.inner.lock().unwrap().games.get(&id).map(|hosted| hosted.game.clone())
+    }
+
+    /// The PTN result, once the game is over
+    pub fn result(&self, id: GameId) -> Option<String> {
+        self.inner.lock().unwrap().games.get(&id).and_then(|hosted| hosted.result.clone())
+    }
+
+    /// The seat a player has in a game, if any
+    pub fn seat(&self, id: GameId, identity: &str) -> Option<Player> {
+        self.inner.lock().unwrap().games.get(&id).and_then(|hosted| hosted.seat(identity))
+    }
+
+    /// The ids of every game a player is seated in, oldest first
+    pub fn games_of(&self, identity: &str) -> Vec<GameId> {
+        let inner = self.inner.lock().unwrap();
+        let mut ids = inner.games.iter()
+                           .filter(|&(_, hosted)| hosted.seat(identity).is_some())
+                           .map(|(id, _)| *id)
+                           .collect::<Vec<_>>();
+        ids.sort();
+        ids
+    }
+
+    /// Stops hosting a game, returning it
+    pub fn remove(&self, id: GameId) -> Option<Game> {
+        self.inner.lock().unwrap().games.remove(&id).map(|hosted| hosted.game)
+    }
+}
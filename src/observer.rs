@@ -0,0 +1,75 @@
+use std::fmt;
+
+use rustc_serialize::Decodable;
+use rustc_serialize::Decoder;
+use rustc_serialize::Encodable;
+use rustc_serialize::Encoder;
+
+use game::Game;
+use piece::Player;
+use turn::Turn;
+
+/// Something that happened to a `Game`, passed to its observers along with
+/// the game as it is afterwards
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Event {
+    Moved(Turn),
+    /// The player who just moved threatens a road
+    Tak(Player),
+    /// The game ended, with its PTN result
+    Over(String),
+    /// This turn was taken back
+    Undone(Turn),
+}
+
+pub type Observer = Box<FnMut(&Event, &Game) + Send>;
+
+/// The callbacks registered on a game
+///
+/// Observers belong to one game: clones of the game (such as the ones the
+/// AI searches with) start without any, and they aren't serialized.
+#[derive(Default)]
+pub struct Observers {
+    observers: Vec<Observer>,
+}
+
+impl Observers {
+    pub fn push(&mut self, observer: Observer) {
+        self.observers.push(observer);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.observers.is_empty()
+    }
+
+    pub fn notify(&mut self, event: &Event, game: &Game) {
+        for observer in self.observers.iter_mut() {
+            observer(event, game);
+        }
+    }
+}
+
+impl Clone for Observers {
+    fn clone(&self) -> Observers {
+        Observers::default()
+    }
+}
+
+impl fmt::Debug for Observers {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Observers({})", self.observers.len())
+    }
+}
+
+impl Encodable for Observers {
+    fn encode<S: Encoder>(&self, s: &mut S) -> Result<(), S::Error> {
+        s.emit_nil()
+    }
+}
+
+impl Decodable for Observers {
+    fn decode<D: Decoder>(d: &mut D) -> Result<Observers, D::Error> {
+        try!(d.read_nil());
+        Ok(Observers::default())
+    }
+}
@@ -0,0 +1,444 @@
+use std::fmt;
+use std::io;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Write;
+use std::net::TcpStream;
+use std::net::ToSocketAddrs;
+use std::str::FromStr;
+
+use ai::strategy::Strategy;
+use game::Game;
+use piece::Player;
+use piece::Stone;
+use point::Point;
+use turn::Direction;
+use turn::Turn;
+
+/// A game someone is looking for an opponent for
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Seek {
+    pub size: usize,
+    /// Seconds on each clock at the start
+    pub time: u64,
+    /// Seconds added after each move
+    pub increment: u64,
+    /// The color the seeker wants to play, if they care
+    pub color: Option<Player>,
+}
+
+/// The start of one of our games
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GameStart {
+    pub id: u32,
+    pub size: usize,
+    pub white: String,
+    pub black: String,
+    /// The color we play
+    pub color: Player,
+    pub time: u64,
+}
+
+/// A line from the PlayTak server
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Message {
+    /// Logged in with this name
+    Welcome(String),
+    SeekNew { id: u32, name: String, seek: Seek },
+    SeekRemove { id: u32 },
+    GameStart(GameStart),
+    Move { game: u32, turn: Turn },
+    /// Seconds left on each clock
+    Time { game: u32, white: u64, black: u64 },
+    Over { game: u32, result: String },
+    Ok,
+    Nok,
+    /// Anything this module doesn't need to understand, such as chat
+    Other(String),
+}
+
+/// A line to the PlayTak server
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Command {
+    Client(String),
+    /// An empty password logs in as a guest
+    Login { name: String, password: String },
+    Seek(Seek),
+    Accept(u32),
+    Move { game: u32, turn: Turn },
+    Resign(u32),
+    Quit,
+}
+
+/// Writes a turn in PlayTak notation, such as `P A1 W` or `M A1 C1 1 2`
+pub fn encode_turn(turn: &Turn) -> String {
+    match *turn {
+        Turn::Place { ref point, ref stone } => {
+            let square = square_name(point);
+            match *stone {
+                Stone::Flat => format!("P {}", square),
+                Stone::Standing => format!("P {} W", square),
+                Stone::Capstone => format!("P {} C", square),
+            }
+        }
+        Turn::Slide { ref point, ref direction, ref drops, .. } => {
+            let (x, y) = (point.x as isize, point.y as isize);
+            let distance = drops.len() as isize;
+            let (end_x, end_y) = match *direction {
+                Direction::Right => (x + distance, y),
+                Direction::Left => (x - distance, y),
+                Direction::Up => (x, y + distance),
+                Direction::Down => (x, y - distance),
+            };
+            let drops = drops.iter().map(|d| d.to_string()).collect::<Vec<_>>();
+            format!("M {} {}{} {}",
+                    square_name(point),
+                    (b'A' as isize + end_x) as u8 as char,
+                    end_y + 1,
+                    drops.join(" "))
+        }
+    }
+}
+
+/// Reads a turn in PlayTak notation, the reverse of `encode_turn`
+pub fn parse_turn(s: &str) -> Result<Turn, String> {
+    let words = s.split_whitespace().collect::<Vec<_>>();
+    match words.first().cloned() {
+        Some("P") if words.len() == 2 || words.len() == 3 => {
+            let stone = match words.get(2).cloned() {
+                None => Stone::Flat,
+                Some("W") => Stone::Standing,
+                Some("C") => Stone::Capstone,
+                Some(other) => return Err(format!("Invalid stone: {}", other)),
+            };
+            Ok(Turn::Place { point: try!(parse_square(words[1])), stone: stone })
+        }
+        Some("M") if words.len() >= 4 => {
+            let start = try!(parse_square(words[1]));
+            let end = try!(parse_square(words[2]));
+            let mut drops = vec![];
+            for word in words[3..].iter() {
+                match word.parse::<usize>() {
+                    Ok(drop) if drop > 0 => drops.push(drop),
+                    _ => return Err(format!("Invalid drop: {}", word)),
+                }
+            }
+            let (direction, distance) = if start.y == end.y && end.x > start.x {
+                (Direction::Right, end.x - start.x)
+            } else if start.y == end.y && end.x < start.x {
+                (Direction::Left, start.x - end.x)
+            } else if start.x == end.x && end.y > start.y {
+                (Direction::Up, end.y - start.y)
+            } else if start.x == end.x && end.y < start.y {
+                (Direction::Down, start.y - end.y)
+            } else {
+                return Err(format!("{} and {} aren't in a line", words[1], words[2]));
+            };
+            if distance != drops.len() {
+                return Err("Need one drop for each square moved".into());
+            }
+            Ok(Turn::Slide {
+                num_pieces: drops.iter().fold(0, |sum, x| sum + x),
+                point: start,
+                direction: direction,
+                drops: drops,
+            })
+        }
+        _ => Err(format!("Invalid move: {}", s)),
+    }
+}
+
+fn square_name(point: &Point) -> String {
+    point.to_string().to_uppercase()
+}
+
+fn parse_square(s: &str) -> Result<Point, String> {
+    s.to_lowercase().parse::<Point>().map_err(|_| format!("Invalid square: {}", s))
+}
+
+fn parse_color(s: &str) -> Result<Option<Player>, String> {
+    match s {
+        "W" | "white" => Ok(Some(Player::One)),
+        "B" | "black" => Ok(Some(Player::Two)),
+        "A" => Ok(None),
+        _ => Err(format!("Invalid color: {}", s)),
+    }
+}
+
+fn number<T: FromStr>(words: &[&str], index: usize) -> Result<T, String> {
+    match words.get(index).map(|word| word.parse::<T>()) {
+        Some(Ok(value)) => Ok(value),
+        _ => Err(format!("Expected a number in: {}", words.join(" "))),
+    }
+}
+
+impl FromStr for Message {
+    type Err = String;
+
+    /// Parses a line from the server
+    ///
+    /// Later versions of the server add fields to the end of some messages,
+    /// so fields past the ones kept here are ignored.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let line = s.trim();
+        let words = line.split_whitespace().collect::<Vec<_>>();
+        if words.first().map(|w| w.starts_with("Game#")).unwrap_or(false) {
+            let game = try!(words[0]["Game#".len()..].parse::<u32>().map_err(|_| format!("Invalid game: {}", words[0])));
+            return match words.get(1).cloned() {
+                Some("P") | Some("M") => {
+                    Ok(Message::Move { game: game, turn: try!(parse_turn(&words[1..].join(" "))) })
+                }
+                Some("Time") => {
+                    Ok(Message::Time {
+                        game: game,
+                        white: try!(number(&words, 2)),
+                        black: try!(number(&words, 3)),
+                    })
+                }
+                Some("Over") if words.len() == 3 => Ok(Message::Over { game: game, result: words[2].into() }),
+                _ => Ok(Message::Other(line.into())),
+            };
+        }
+        match (words.get(0).cloned(), words.get(1).cloned()) {
+            (Some("Welcome"), Some(name)) if name.ends_with('!') && words.len() == 2 => {
+                Ok(Message::Welcome(name[..name.len() - 1].into()))
+            }
+            (Some("Seek"), Some("new")) if words.len() >= 7 => {
+                Ok(Message::SeekNew {
+                    id: try!(number(&words, 2)),
+                    name: words[3].into(),
+                    seek: Seek {
+                        size: try!(number(&words, 4)),
+                        time: try!(number(&words, 5)),
+                        increment: try!(number(&words, 6)),
+                        color: match words.get(7) {
+                            Some(color) => try!(parse_color(color)),
+                            None => None,
+                        },
+                    },
+                })
+            }
+            (Some("Seek"), Some("remove")) if words.len() >= 3 => {
+                Ok(Message::SeekRemove { id: try!(number(&words, 2)) })
+            }
+            (Some("Game"), Some("Start")) if words.len() >= 9 && words[5] == "vs" => {
+                Ok(Message::GameStart(GameStart {
+                    id: try!(number(&words, 2)),
+                    size: try!(number(&words, 3)),
+                    white: words[4].into(),
+                    black: words[6].into(),
+                    color: try!(try!(parse_color(words[7])).ok_or(format!("Invalid color: {}", words[7]))),
+                    time: try!(number(&words, 8)),
+                }))
+            }
+            (Some("OK"), None) => Ok(Message::Ok),
+            (Some("NOK"), None) => Ok(Message::Nok),
+            _ => Ok(Message::Other(line.into())),
+        }
+    }
+}
+
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Message::Welcome(ref name) => write!(f, "Welcome {}!", name),
+            Message::SeekNew { id, ref name, ref seek } => {
+                try!(write!(f, "Seek new {} {} {} {} {}", id, name, seek.size, seek.time, seek.increment));
+                write!(f, " {}", color_code(seek.color))
+            }
+            Message::SeekRemove { id } => write!(f, "Seek remove {}", id),
+            Message::GameStart(ref start) => {
+                write!(f, "Game Start {} {} {} vs {} {} {}",
+                       start.id,
+                       start.size,
+                       start.white,
+                       start.black,
+                       if start.color == Player::One { "white" } else { "black" },
+                       start.time)
+            }
+            Message::Move { game, ref turn } => write!(f, "Game#{} {}", game, encode_turn(turn)),
+            Message::Time { game, white, black } => write!(f, "Game#{} Time {} {}", game, white, black),
+            Message::Over { game, ref result } => write!(f, "Game#{} Over {}", game, result),
+            Message::Ok => write!(f, "OK"),
+            Message::Nok => write!(f, "NOK"),
+            Message::Other(ref line) => write!(f, "{}", line),
+        }
+    }
+}
+
+fn color_code(color: Option<Player>) -> &'static str {
+    match color {
+        Some(Player::One) => "W",
+        Some(Player::Two) => "B",
+        None => "A",
+    }
+}
+
+impl FromStr for Command {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let line = s.trim();
+        let words = line.split_whitespace().collect::<Vec<_>>();
+        if words.first().map(|w| w.starts_with("Game#")).unwrap_or(false) {
+            let game = try!(words[0]["Game#".len()..].parse::<u32>().map_err(|_| format!("Invalid game: {}", words[0])));
+            return match words.get(1).cloned() {
+                Some("Resign") => Ok(Command::Resign(game)),
+                _ => Ok(Command::Move { game: game, turn: try!(parse_turn(&words[1..].join(" "))) }),
+            };
+        }
+        match words.first().cloned() {
+            Some("Client") if words.len() == 2 => Ok(Command::Client(words[1].into())),
+            Some("Login") if words.len() == 2 || words.len() == 3 => {
+                Ok(Command::Login {
+                    name: words[1].into(),
+                    password: words.get(2).cloned().unwrap_or("").into(),
+                })
+            }
+            Some("Seek") if words.len() == 4 || words.len() == 5 => {
+                Ok(Command::Seek(Seek {
+                    size: try!(number(&words, 1)),
+                    time: try!(number(&words, 2)),
+                    increment: try!(number(&words, 3)),
+                    color: match words.get(4) {
+                        Some(color) => try!(parse_color(color)),
+                        None => None,
+                    },
+                }))
+            }
+            Some("Accept") if words.len() == 2 => Ok(Command::Accept(try!(number(&words, 1)))),
+            Some("quit") if words.len() == 1 => Ok(Command::Quit),
+            _ => Err(format!("Unknown command: {}", line)),
+        }
+    }
+}
+
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Command::Client(ref name) => write!(f, "Client {}", name),
+            Command::Login { ref name, ref password } if password.is_empty() => write!(f, "Login {}", name),
+            Command::Login { ref name, ref password } => write!(f, "Login {} {}", name, password),
+            Command::Seek(ref seek) => {
+                try!(write!(f, "Seek {} {} {}", seek.size, seek.time, seek.increment));
+                match seek.color {
+                    Some(color) => write!(f, " {}", color_code(Some(color))),
+                    None => Ok(()),
+                }
+            }
+            Command::Accept(id) => write!(f, "Accept {}", id),
+            Command::Move { game, ref turn } => write!(f, "Game#{} {}", game, encode_turn(turn)),
+            Command::Resign(game) => write!(f, "Game#{} Resign", game),
+            Command::Quit => write!(f, "quit"),
+        }
+    }
+}
+
+/// How a bot's game ended
+#[derive(Clone, Debug)]
+pub struct Played {
+    pub id: u32,
+    pub game: Game,
+    /// The result as the server gave it, such as `R-0`
+    pub result: String,
+}
+
+/// A bot that logs in, seeks a game and plays it with a strategy
+pub struct Bot<S: Strategy> {
+    name: String,
+    password: String,
+    seek: Seek,
+    strategy: S,
+}
+
+impl<S: Strategy> Bot<S> {
+    /// A bot seeking a ten minute 5x5 game with either color
+    pub fn new(name: &str, password: &str, strategy: S) -> Bot<S> {
+        Bot {
+            name: name.into(),
+            password: password.into(),
+            seek: Seek {
+                size: 5,
+                time: 600,
+                increment: 0,
+                color: None,
+            },
+            strategy: strategy,
+        }
+    }
+
+    pub fn with_seek(mut self, seek: Seek) -> Bot<S> {
+        self.seek = seek;
+        self
+    }
+
+    /// Plays one game on the server at addr
+    pub fn connect<A: ToSocketAddrs>(&mut self, addr: A) -> io::Result<Played> {
+        let stream = try!(TcpStream::connect(addr));
+        let reader = BufReader::new(try!(stream.try_clone()));
+        self.play(reader, stream)
+    }
+
+    /// Plays one game over any connection to the server, and returns once
+    /// the server says it is over
+    pub fn play<R: BufRead, W: Write>(&mut self, reader: R, mut writer: W) -> io::Result<Played> {
+        let mut lines = reader.lines();
+        let mut next = || -> io::Result<Message> {
+            match lines.next() {
+                Some(line) => try!(line).parse::<Message>().map_err(invalid),
+                None => Err(io::Error::new(io::ErrorKind::UnexpectedEof, "Server closed the connection")),
+            }
+        };
+
+        try!(send(&mut writer, &Command::Client(format!("tak-rs-{}", env!("CARGO_PKG_VERSION")))));
+        try!(send(&mut writer, &Command::Login { name: self.name.clone(), password: self.password.clone() }));
+        loop {
+            match try!(next()) {
+                Message::Welcome(_) => break,
+                Message::Nok => return Err(invalid("Login rejected".into())),
+                _ => {}
+            }
+        }
+
+        try!(send(&mut writer, &Command::Seek(self.seek.clone())));
+        let start = loop {
+            if let Message::GameStart(start) = try!(next()) {
+                break start;
+            }
+        };
+        if start.size < 3 || start.size > 8 {
+            return Err(invalid(format!("Unsupported board size {}", start.size)));
+        }
+
+        let mut game = Game::new(start.size);
+        loop {
+            if game.check_winner().is_none() && game.next_player() == start.color {
+                let turn = self.strategy.choose(&game);
+                try!(game.play_turn(turn.clone()).map_err(invalid));
+                try!(send(&mut writer, &Command::Move { game: start.id, turn: turn }));
+            }
+            match try!(next()) {
+                Message::Move { game: id, turn } if id == start.id => {
+                    try!(game.play_turn(turn).map_err(invalid));
+                }
+                Message::Over { game: id, result } if id == start.id => {
+                    return Ok(Played {
+                        id: id,
+                        game: game,
+                        result: result,
+                    });
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+fn send<W: Write>(writer: &mut W, command: &Command) -> io::Result<()> {
+    try!(writeln!(writer, "{}", command));
+    writer.flush()
+}
+
+fn invalid(e: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e)
+}
@@ -0,0 +1,232 @@
+use std::fmt;
+use std::str::FromStr;
+
+use game::Game;
+use turn::Turn;
+
+/// A game written in Portable Tak Notation
+///
+/// Move numbers, comments, result markers and annotations (`'`, `!`, `?`)
+/// are skipped; only the tags and the turns are kept.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Ptn {
+    pub tags: Vec<(String, String)>,
+    pub moves: Vec<Turn>,
+}
+
+impl Ptn {
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags.iter()
+                 .find(|&&(ref key, _)| key == name)
+                 .map(|&(_, ref value)| value.as_str())
+    }
+
+    pub fn size(&self) -> Option<usize> {
+        self.tag("Size").and_then(|s| s.parse::<usize>().ok())
+    }
+
+    /// Plays every move on a new board of the tagged size, or from the
+    /// position in the TPS tag if there is one
+    pub fn game(&self) -> Result<Game, String> {
+        let size = try!(self.size().ok_or("Missing or invalid Size tag".to_string()));
+        if size < 3 || size > 8 {
+            return Err("Unsupported board size".into());
+        }
+        let mut game = match self.tag("TPS") {
+            Some(tps) => try!(Game::from_tps(tps)),
+            None => Game::new(size),
+        };
+        if game.size() != size {
+            return Err("TPS doesn't match the Size tag".into());
+        }
+        for turn in self.moves.iter() {
+            try!(game.play_turn(turn.clone()));
+        }
+        Ok(game)
+    }
+}
+
+/// Where a PTN file goes wrong, with 1-based line and column numbers
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Problem {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl Problem {
+    fn new(line: usize, column: usize, message: String) -> Problem {
+        Problem {
+            line: line,
+            column: column,
+            message: message,
+        }
+    }
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}, column {}: {}", self.line, self.column, self.message)
+    }
+}
+
+/// Replays a PTN file as a referee would, returning the finished game or
+/// the first problem
+///
+/// Every move must parse and be legal, no move may come after the game is
+/// over, and the `Result` tag (and any result at the end of the moves) must
+/// match what happened on the board. Results the board can't show, such as
+/// `0-1` after a resignation, are accepted while the game is still going.
+pub fn check(s: &str) -> Result<Game, Problem> {
+    let items = try!(scan(s));
+    let mut tags = vec![];
+    let mut moves = vec![];
+    let mut results = vec![];
+    for item in items {
+        match item {
+            (line, column, Item::Tag(key, value)) => tags.push((line, column, key, value)),
+            (line, column, Item::Word(word)) => {
+                if is_result(&word) {
+                    results.push((line, column, word));
+                } else if let Some(turn) = try!(parse_word(line, column, &word)) {
+                    moves.push((line, column, word, turn));
+                }
+            }
+        }
+    }
+
+    let ptn = Ptn {
+        tags: tags.iter().map(|&(_, _, ref key, ref value)| (key.clone(), value.clone())).collect(),
+        moves: vec![],
+    };
+    let mut game = try!(ptn.game().map_err(|e| Problem::new(1, 1, e)));
+    for (line, column, word, turn) in moves {
+        if game.check_winner().is_some() {
+            return Err(Problem::new(line, column, format!("{} comes after the game is over", word)));
+        }
+        if let Err(e) = game.play_turn(turn) {
+            return Err(Problem::new(line, column, format!("Illegal move {}: {}", word, e)));
+        }
+    }
+
+    let (line, column, declared) = match tags.iter().find(|&&(_, _, ref key, _)| key == "Result") {
+        Some(&(line, column, _, ref value)) => (line, column, value.clone()),
+        None => return Err(Problem::new(1, 1, "Missing Result tag".into())),
+    };
+    let actual = game.result();
+    let matches = if actual.is_empty() {
+        ["", "0-0", "1-0", "0-1", "1/2-1/2"].contains(&declared.as_str())
+    } else {
+        declared == actual
+    };
+    if !matches {
+        let happened = if actual.is_empty() { "the game isn't over" } else { actual };
+        return Err(Problem::new(line, column, format!("Result is {}, but {}", declared, happened)));
+    }
+    for (line, column, word) in results {
+        if word != declared {
+            return Err(Problem::new(line, column, format!("{} doesn't match the Result tag {}", word, declared)));
+        }
+    }
+    Ok(game)
+}
+
+enum Item {
+    Tag(String, String),
+    Word(String),
+}
+
+// Splits PTN into tags and whitespace separated words, leaving out
+// comments, with the line and column each starts at
+fn scan(s: &str) -> Result<Vec<(usize, usize, Item)>, Problem> {
+    let mut items = vec![];
+    let mut in_comment = false;
+
+    for (index, line) in s.lines().enumerate() {
+        let trimmed = line.trim();
+        if !in_comment && trimmed.starts_with('[') {
+            let column = line.chars().take_while(|c| c.is_whitespace()).count() + 1;
+            let (key, value) = try!(parse_tag(trimmed).map_err(|e| Problem::new(index + 1, column, e)));
+            items.push((index + 1, column, Item::Tag(key, value)));
+            continue;
+        }
+
+        let mut word = String::new();
+        let mut start = 0;
+        for (i, c) in line.chars().enumerate() {
+            let ends_word = in_comment || c == '{' || c == '}' || c.is_whitespace();
+            if ends_word && !word.is_empty() {
+                items.push((index + 1, start, Item::Word(word.clone())));
+                word.clear();
+            }
+            match c {
+                '{' => in_comment = true,
+                '}' => in_comment = false,
+                _ if ends_word => {}
+                _ => {
+                    if word.is_empty() {
+                        start = i + 1;
+                    }
+                    word.push(c);
+                }
+            }
+        }
+        if !word.is_empty() {
+            items.push((index + 1, start, Item::Word(word)));
+        }
+    }
+    Ok(items)
+}
+
+// The turn in a word of movetext, or nothing for move numbers and results
+fn parse_word(line: usize, column: usize, word: &str) -> Result<Option<Turn>, Problem> {
+    // `--` stands in for player one's move when a game starts with player
+    // two to move
+    if word.ends_with('.') || word == "--" || is_result(word) {
+        return Ok(None);
+    }
+    let stripped = word.trim_end_matches(|c| "'\"!?*".contains(c));
+    match stripped.parse::<Turn>() {
+        Ok(turn) if !stripped.is_empty() => Ok(Some(turn)),
+        _ => Err(Problem::new(line, column, format!("Invalid move: {}", word))),
+    }
+}
+
+fn parse_tag(line: &str) -> Result<(String, String), String> {
+    let inner = line.trim_start_matches('[').trim_end_matches(']');
+    let mut parts = inner.splitn(2, ' ');
+    let key = parts.next().unwrap_or("");
+    let value = parts.next().unwrap_or("").trim();
+    if key.is_empty() || !value.starts_with('"') || !value.ends_with('"') || value.len() < 2 {
+        return Err(format!("Invalid tag: {}", line));
+    }
+    Ok((key.into(), value[1..value.len() - 1].into()))
+}
+
+fn is_result(token: &str) -> bool {
+    ["R-0", "0-R", "F-0", "0-F", "1-0", "0-1", "1/2-1/2", "0-0"].contains(&token)
+}
+
+impl FromStr for Ptn {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut tags = vec![];
+        let mut moves = vec![];
+        for item in try!(scan(s).map_err(|e| e.to_string())) {
+            match item {
+                (_, _, Item::Tag(key, value)) => tags.push((key, value)),
+                (line, column, Item::Word(word)) => {
+                    if let Some(turn) = try!(parse_word(line, column, &word).map_err(|e| e.to_string())) {
+                        moves.push(turn);
+                    }
+                }
+            }
+        }
+
+        Ok(Ptn {
+            tags: tags,
+            moves: moves,
+        })
+    }
+}
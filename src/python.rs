@@ -0,0 +1,362 @@
+//! Python bindings, enabled by the `python` feature
+//!
+//! Built into a wheel by maturin (see `pyproject.toml`), which installs as
+//! the `tak` module. Players are 1 or 2 and stones are `F`, `S` or `C`, as
+//! in PTN, and anything that doesn't parse or isn't legal raises
+//! `ValueError`.
+
+use std::time::Duration;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use ai;
+use board::Board;
+use game::Game;
+use piece::Player;
+use point::Point;
+use ptn::Ptn;
+use turn::Turn;
+
+fn error(e: String) -> PyErr {
+    PyValueError::new_err(e)
+}
+
+fn number(player: Player) -> u8 {
+    match player {
+        Player::One => 1,
+        Player::Two => 2,
+    }
+}
+
+/// A square, such as `a1`, with x and y counted from 0
+#[pyclass(name = "Point", module = "tak")]
+#[derive(Clone, Copy)]
+pub struct PyPoint {
+    point: Point,
+}
+
+#[pymethods]
+impl PyPoint {
+    #[new]
+    fn new(x: usize, y: usize) -> PyResult<PyPoint> {
+        if x >= 8 || y >= 8 {
+            return Err(error(format!("No square at ({}, {})", x, y)));
+        }
+        Ok(PyPoint { point: Point::new(x, y) })
+    }
+
+    #[staticmethod]
+    fn parse(square: &str) -> PyResult<PyPoint> {
+        square.parse::<Point>()
+              .map(|point| PyPoint { point: point })
+              .map_err(|_| error(format!("Invalid square: {}", square)))
+    }
+
+    #[getter]
+    fn x(&self) -> usize {
+        self.point.x
+    }
+
+    #[getter]
+    fn y(&self) -> usize {
+        self.point.y
+    }
+
+    fn __eq__(&self, other: &PyPoint) -> bool {
+        self.point == other.point
+    }
+
+    fn __hash__(&self) -> u64 {
+        (self.point.x * 8 + self.point.y) as u64
+    }
+
+    fn __str__(&self) -> String {
+        self.point.to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Point.parse('{}')", self.point)
+    }
+}
+
+/// A move: a placement or a slide
+#[pyclass(name = "Turn", module = "tak")]
+#[derive(Clone)]
+pub struct PyTurn {
+    turn: Turn,
+}
+
+#[pymethods]
+impl PyTurn {
+    #[staticmethod]
+    fn parse(ptn: &str) -> PyResult<PyTurn> {
+        ptn.parse::<Turn>()
+           .map(|turn| PyTurn { turn: turn })
+           .map_err(|_| error(format!("Invalid move: {}", ptn)))
+    }
+
+    /// `place` or `slide`
+    #[getter]
+    fn kind(&self) -> &'static str {
+        match self.turn {
+            Turn::Place { .. } => "place",
+            Turn::Slide { .. } => "slide",
+        }
+    }
+
+    /// The square placed on or slid from
+    #[getter]
+    fn point(&self) -> PyPoint {
+        match self.turn {
+            Turn::Place { point, .. } | Turn::Slide { point, .. } => PyPoint { point: point },
+        }
+    }
+
+    /// The stone placed, or None for a slide
+    #[getter]
+    fn stone(&self) -> Option<String> {
+        match self.turn {
+            Turn::Place { stone, .. } => Some(stone.to_string()),
+            Turn::Slide { .. } => None,
+        }
+    }
+
+    /// `>`, `<`, `+` or `-` for a slide, or None for a placement
+    #[getter]
+    fn direction(&self) -> Option<String> {
+        match self.turn {
+            Turn::Place { .. } => None,
+            Turn::Slide { direction, .. } => Some(direction.to_string()),
+        }
+    }
+
+    /// The pieces dropped on each square of a slide, or empty for a
+    /// placement
+    #[getter]
+    fn drops(&self) -> Vec<usize> {
+        match self.turn {
+            Turn::Place { .. } => vec![],
+            Turn::Slide { ref drops, .. } => drops.clone(),
+        }
+    }
+
+    fn __eq__(&self, other: &PyTurn) -> bool {
+        self.turn == other.turn
+    }
+
+    fn __str__(&self) -> String {
+        self.turn.to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Turn.parse('{}')", self.turn)
+    }
+}
+
+/// A move given as PTN or as a `Turn`
+#[derive(FromPyObject)]
+enum Move {
+    Turn(PyTurn),
+    Ptn(String),
+}
+
+impl Move {
+    fn turn(self) -> PyResult<Turn> {
+        match self {
+            Move::Turn(turn) => Ok(turn.turn),
+            Move::Ptn(ptn) => PyTurn::parse(&ptn).map(|turn| turn.turn),
+        }
+    }
+}
+
+/// A square given as a name, such as `a1`, or as a `Point`
+#[derive(FromPyObject)]
+enum Square {
+    Point(PyPoint),
+    Name(String),
+}
+
+impl Square {
+    fn point(self) -> PyResult<Point> {
+        match self {
+            Square::Point(point) => Ok(point.point),
+            Square::Name(name) => PyPoint::parse(&name).map(|point| point.point),
+        }
+    }
+}
+
+/// A game of Tak
+#[pyclass(name = "Game", module = "tak")]
+#[derive(Clone)]
+pub struct PyGame {
+    game: Game,
+}
+
+#[pymethods]
+impl PyGame {
+    /// An empty board, from 3x3 to 8x8
+    #[new]
+    #[pyo3(signature = (size = 5))]
+    fn new(size: usize) -> PyResult<PyGame> {
+        if size < 3 || size > 8 {
+            return Err(error(format!("Unsupported board size {}", size)));
+        }
+        Ok(PyGame { game: Game::new(size) })
+    }
+
+    #[staticmethod]
+    fn from_tps(tps: &str) -> PyResult<PyGame> {
+        Game::from_tps(tps).map(|game| PyGame { game: game }).map_err(error)
+    }
+
+    /// Replays a PTN game, failing on the first illegal move
+    #[staticmethod]
+    fn from_ptn(ptn: &str) -> PyResult<PyGame> {
+        let ptn = try!(ptn.parse::<Ptn>().map_err(error));
+        ptn.game().map(|game| PyGame { game: game }).map_err(error)
+    }
+
+    /// Plays a move for the player to move, returning the winner if it
+    /// ended the game
+    fn play(&mut self, turn: Move) -> PyResult<Option<u8>> {
+        if self.game.check_winner().is_some() {
+            return Err(error("The game is over".into()));
+        }
+        let turn = try!(turn.turn());
+        self.game.play_turn(turn).map(|winner| winner.map(number)).map_err(error)
+    }
+
+    /// Takes back the last move, returning it
+    fn undo(&mut self) -> Option<PyTurn> {
+        self.game.undo().map(|turn| PyTurn { turn: turn })
+    }
+
+    /// Every legal move, empty once the game is over
+    fn legal_moves(&self) -> Vec<PyTurn> {
+        if self.game.check_winner().is_some() {
+            return vec![];
+        }
+        self.game.legal_moves().into_iter().map(|turn| PyTurn { turn: turn }).collect()
+    }
+
+    fn is_legal(&self, turn: Move) -> bool {
+        match turn.turn() {
+            Ok(turn) => self.game.check_winner().is_none() && self.game.clone().play_turn(turn).is_ok(),
+            Err(_) => false,
+        }
+    }
+
+    /// The moves played so far
+    fn history(&self) -> Vec<PyTurn> {
+        self.game.history().iter().map(|turn| PyTurn { turn: turn.clone() }).collect()
+    }
+
+    /// The pieces on a square from the bottom up, as `(player, stone)`
+    fn stack(&self, square: Square) -> PyResult<Vec<(u8, String)>> {
+        let point = try!(square.point());
+        let pieces = try!(self.game.board().at(&point).map_err(|e| error(e.into())));
+        Ok(pieces.map(|piece| (number(piece.owner()), piece.stone().to_string())).collect())
+    }
+
+    /// The piece on top of a square, or None if it's empty
+    fn top(&self, square: Square) -> PyResult<Option<(u8, String)>> {
+        self.stack(square).map(|mut stack| stack.pop())
+    }
+
+    fn is_tak(&self) -> bool {
+        self.game.is_tak()
+    }
+
+    /// 1 or 2 once someone has won, otherwise None
+    fn winner(&self) -> Option<u8> {
+        self.game.check_winner().map(number)
+    }
+
+    /// The PTN result, or an empty string while the game goes on
+    fn result(&self) -> &'static str {
+        self.game.result()
+    }
+
+    fn to_tps(&self) -> String {
+        self.game.to_tps()
+    }
+
+    #[pyo3(signature = (player1 = "", player2 = ""))]
+    fn to_ptn(&self, player1: &str, player2: &str) -> String {
+        self.game.as_ptn_with_players(player1, player2)
+    }
+
+    #[getter]
+    fn size(&self) -> usize {
+        self.game.size()
+    }
+
+    #[getter]
+    fn turn_number(&self) -> usize {
+        self.game.turn_number()
+    }
+
+    #[getter]
+    fn next_player(&self) -> u8 {
+        number(self.game.next_player())
+    }
+
+    fn __str__(&self) -> String {
+        self.game.to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Game.from_tps('{}')", self.game.to_tps())
+    }
+}
+
+/// The AI, searching depth plies or until the time limit in seconds
+#[pyclass(name = "Ai", module = "tak")]
+pub struct PyAi {
+    depth: usize,
+    time_limit: Option<f64>,
+    seed: u64,
+}
+
+#[pymethods]
+impl PyAi {
+    #[new]
+    #[pyo3(signature = (depth = 3, time_limit = None, seed = 0))]
+    fn new(depth: usize, time_limit: Option<f64>, seed: u64) -> PyResult<PyAi> {
+        if depth == 0 {
+            return Err(error("The depth must be at least 1".into()));
+        }
+        if time_limit.map_or(false, |limit| !(limit > 0.0)) {
+            return Err(error("The time limit must be positive".into()));
+        }
+        Ok(PyAi {
+            depth: depth,
+            time_limit: time_limit,
+            seed: seed,
+        })
+    }
+
+    /// The move to play for whoever is to move in game
+    fn best_move(&self, py: Python, game: &PyGame) -> PyResult<PyTurn> {
+        if game.game.check_winner().is_some() {
+            return Err(error("The game is over".into()));
+        }
+        let mut ai = ai::Ai::new(game.game.next_player()).with_depth(self.depth).with_seed(self.seed);
+        if let Some(limit) = self.time_limit {
+            ai = ai.with_time_limit(Duration::from_millis((limit * 1000.0) as u64));
+        }
+        let game = game.game.clone();
+        // Let other Python threads run while searching
+        Ok(PyTurn { turn: py.allow_threads(move || ai.best_move(&game)) })
+    }
+}
+
+#[pymodule]
+fn tak(module: &Bound<PyModule>) -> PyResult<()> {
+    try!(module.add_class::<PyPoint>());
+    try!(module.add_class::<PyTurn>());
+    try!(module.add_class::<PyGame>());
+    try!(module.add_class::<PyAi>());
+    Ok(())
+}
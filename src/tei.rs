@@ -0,0 +1,288 @@
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::mpsc::Sender;
+use std::thread;
+use std::thread::JoinHandle;
+use std::time::Duration;
+use std::time::Instant;
+
+use ai::eval::Weights;
+use ai::search::FORCED;
+use ai::search::Searcher;
+use ai::search::TranspositionTable;
+use ai::search::WIN;
+use game::Game;
+use piece::Player;
+use piece::Stone;
+use turn::Turn;
+
+/// Deepest a search goes when nothing else limits it
+const MAX_DEPTH: usize = 64;
+
+/// An engine speaking the Tak Engine Interface, a UCI-like text protocol
+///
+/// Each command is passed to `handle` as a line, and every reply is sent as
+/// a line on the channel. Searches run on their own thread, so `stop` can
+/// interrupt them; their `info` and `bestmove` lines arrive whenever they
+/// finish.
+pub struct Tei {
+    out: Sender<String>,
+    size: usize,
+    game: Game,
+    weights: Weights,
+    stop: Arc<AtomicBool>,
+    search: Option<JoinHandle<()>>,
+}
+
+/// What ends a search, from the arguments to `go`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct GoLimits {
+    depth: Option<usize>,
+    movetime: Option<u64>,
+    wtime: Option<u64>,
+    btime: Option<u64>,
+    winc: u64,
+    binc: u64,
+}
+
+impl Tei {
+    pub fn new(out: Sender<String>) -> Tei {
+        Tei {
+            out: out,
+            size: 5,
+            game: Game::new(5),
+            weights: Weights::default(),
+            stop: Arc::new(AtomicBool::new(false)),
+            search: None,
+        }
+    }
+
+    /// Evaluates positions with these weights, such as ones from `tak tune`
+    pub fn with_weights(mut self, weights: Weights) -> Tei {
+        self.weights = weights;
+        self
+    }
+
+    /// Handles one line of input, returning false once the engine should
+    /// quit
+    ///
+    /// Commands that can't be understood are answered with an
+    /// `info string` line explaining why.
+    pub fn handle(&mut self, line: &str) -> bool {
+        let words = line.split_whitespace().collect::<Vec<_>>();
+        let result = match words.first().cloned() {
+            Some("tei") => {
+                self.send(format!("id name tak-rs {}", env!("CARGO_PKG_VERSION")));
+                self.send(format!("id author {}", env!("CARGO_PKG_AUTHORS")));
+                self.send("option name Size type spin default 5 min 3 max 8".into());
+                self.send("teiok".into());
+                Ok(())
+            }
+            Some("isready") => {
+                self.send("readyok".into());
+                Ok(())
+            }
+            Some("setoption") => self.set_option(&words[1..]),
+            Some("teinew") => {
+                self.finish_search();
+                self.game = Game::new(self.size);
+                Ok(())
+            }
+            Some("position") => {
+                self.finish_search();
+                self.position(&words[1..])
+            }
+            Some("go") => {
+                self.finish_search();
+                self.go(&words[1..])
+            }
+            Some("stop") => {
+                self.finish_search();
+                Ok(())
+            }
+            Some("quit") => {
+                self.finish_search();
+                return false;
+            }
+            Some(command) => Err(format!("Unknown command: {}", command)),
+            None => Ok(()),
+        };
+        if let Err(e) = result {
+            self.send(format!("info string {}", e));
+        }
+        true
+    }
+
+    fn send(&self, line: String) {
+        // Nobody is listening once the receiver is gone, so there's no one
+        // to tell either
+        let _ = self.out.send(line);
+    }
+
+    /// Stops the running search, if any, and waits for its `bestmove`
+    fn finish_search(&mut self) {
+        if let Some(search) = self.search.take() {
+            self.stop.store(true, Ordering::SeqCst);
+            search.join().unwrap();
+        }
+    }
+
+    fn set_option(&mut self, words: &[&str]) -> Result<(), String> {
+        // setoption name <name> value <value>
+        match (words.get(1), words.get(3)) {
+            (Some(&"Size"), Some(value)) => {
+                match value.parse::<usize>() {
+                    Ok(size) if size >= 3 && size <= 8 => {
+                        self.size = size;
+                        Ok(())
+                    }
+                    _ => Err(format!("Invalid size: {}", value)),
+                }
+            }
+            (Some(name), _) => Err(format!("Unknown option: {}", name)),
+            (None, _) => Err("Missing option name".into()),
+        }
+    }
+
+    fn position(&mut self, words: &[&str]) -> Result<(), String> {
+        let (mut game, rest) = match words.first().cloned() {
+            Some("startpos") => (Game::new(self.size), &words[1..]),
+            Some("tps") if words.len() >= 4 => {
+                (try!(Game::from_tps(&words[1..4].join(" "))), &words[4..])
+            }
+            _ => return Err("Expected position startpos or position tps".into()),
+        };
+        match rest.first().cloned() {
+            Some("moves") => {
+                for word in rest[1..].iter() {
+                    let turn = try!(word.parse::<Turn>().map_err(|_| format!("Invalid move: {}", word)));
+                    try!(game.play_turn(turn).map_err(|e| format!("Illegal move {}: {}", word, e)));
+                }
+            }
+            Some(word) => return Err(format!("Unexpected {}", word)),
+            None => {}
+        }
+        self.game = game;
+        Ok(())
+    }
+
+    fn go(&mut self, words: &[&str]) -> Result<(), String> {
+        let limits = try!(GoLimits::parse(words));
+        let depth = limits.depth.unwrap_or(MAX_DEPTH);
+        let deadline = limits.think_time(&self.game).map(|ms| Instant::now() + Duration::from_millis(ms));
+
+        self.stop = Arc::new(AtomicBool::new(false));
+        let stop = self.stop.clone();
+        let out = self.out.clone();
+        let game = self.game.clone();
+        let weights = self.weights;
+        self.search = Some(thread::spawn(move || {
+            search(&game, depth, deadline, weights, &stop, &out);
+        }));
+        Ok(())
+    }
+}
+
+impl Drop for Tei {
+    fn drop(&mut self) {
+        self.finish_search();
+    }
+}
+
+impl GoLimits {
+    fn parse(words: &[&str]) -> Result<GoLimits, String> {
+        let mut limits = GoLimits::default();
+        let mut words = words.iter();
+        while let Some(word) = words.next() {
+            if *word == "infinite" {
+                continue;
+            }
+            let value = match words.next().map(|v| v.parse::<u64>()) {
+                Some(Ok(value)) => value,
+                _ => return Err(format!("Expected a number after {}", word)),
+            };
+            match *word {
+                "depth" => limits.depth = Some(value as usize),
+                "movetime" => limits.movetime = Some(value),
+                "wtime" => limits.wtime = Some(value),
+                "btime" => limits.btime = Some(value),
+                "winc" => limits.winc = value,
+                "binc" => limits.binc = value,
+                _ => return Err(format!("Unknown go argument: {}", word)),
+            }
+        }
+        Ok(limits)
+    }
+
+    /// Milliseconds to spend on this move, if the search is timed
+    ///
+    /// With a clock, a twentieth of the time left and half the increment,
+    /// but never more than half the time left.
+    fn think_time(&self, game: &Game) -> Option<u64> {
+        if self.movetime.is_some() {
+            return self.movetime;
+        }
+        let (left, increment) = match game.next_player() {
+            Player::One => (self.wtime, self.winc),
+            Player::Two => (self.btime, self.binc),
+        };
+        left.map(|left| (left / 20 + increment / 2).min(left / 2))
+    }
+}
+
+/// Deepens one ply at a time, sending an `info` line for every finished
+/// depth and then the `bestmove` line
+fn search(game: &Game, depth: usize, deadline: Option<Instant>, weights: Weights, stop: &AtomicBool,
+          out: &Sender<String>) {
+    if game.check_winner().is_some() {
+        let _ = out.send("info string The game is over".into());
+        return;
+    }
+    let start = Instant::now();
+    let table = TranspositionTable::new();
+    let mut searcher = Searcher::new(&table, stop, 0).with_weights(weights).with_deadline(deadline);
+    let mut best = None;
+    for d in 1..depth + 1 {
+        let (turn, score) = match searcher.root(game, d) {
+            Some(result) => result,
+            None => break,
+        };
+        let pv = table.principal_variation(game, d);
+        let pv = if pv.first() == Some(&turn) { pv } else { vec![turn.clone()] };
+        let elapsed = start.elapsed();
+        let _ = out.send(format!("info depth {} score {} nodes {} time {} pv {}",
+                           d,
+                           score_string(score),
+                           searcher.nodes,
+                           elapsed.as_secs() * 1000 + elapsed.subsec_nanos() as u64 / 1_000_000,
+                           pv.iter().map(move_string).collect::<Vec<_>>().join(" ")));
+        best = Some(turn);
+        if score.abs() >= WIN - d as i64 {
+            break;
+        }
+    }
+    let _ = match best {
+        Some(turn) => out.send(format!("bestmove {}", move_string(&turn))),
+        None => out.send("info string No legal moves".into()),
+    };
+}
+
+/// PTN for a turn, leaving out the `F` of flat placements as engines
+/// usually do
+fn move_string(turn: &Turn) -> String {
+    match *turn {
+        Turn::Place { ref point, stone: Stone::Flat } => point.to_string(),
+        _ => turn.to_string(),
+    }
+}
+
+/// `cp` with a flat worth 100, or `mate` in moves for a forced result
+fn score_string(score: i64) -> String {
+    if score.abs() >= FORCED {
+        let moves = (WIN - score.abs() + 1) / 2;
+        format!("mate {}", if score > 0 { moves } else { -moves })
+    } else {
+        format!("cp {}", score)
+    }
+}
@@ -0,0 +1,105 @@
+//! JavaScript bindings, enabled by the `wasm` feature
+//!
+//! Moves go in and out as PTN strings and players as 1 or 2, so the API
+//! needs nothing beyond strings and numbers on the JavaScript side.
+
+use wasm_bindgen::prelude::*;
+
+use game::Game;
+use piece::Player;
+use ptn::Ptn;
+
+fn error(e: String) -> JsValue {
+    JsValue::from_str(&e)
+}
+
+fn number(player: Player) -> u8 {
+    match player {
+        Player::One => 1,
+        Player::Two => 2,
+    }
+}
+
+/// A game of Tak, exported to JavaScript as `Game`
+#[wasm_bindgen(js_name = Game)]
+pub struct WasmGame {
+    game: Game,
+}
+
+#[wasm_bindgen(js_class = Game)]
+impl WasmGame {
+    /// An empty board, from 3x3 to 8x8
+    #[wasm_bindgen(constructor)]
+    pub fn new(size: usize) -> Result<WasmGame, JsValue> {
+        if size < 3 || size > 8 {
+            return Err(error(format!("Unsupported board size {}", size)));
+        }
+        Ok(WasmGame { game: Game::new(size) })
+    }
+
+    #[wasm_bindgen(js_name = fromTps)]
+    pub fn from_tps(tps: &str) -> Result<WasmGame, JsValue> {
+        Game::from_tps(tps).map(|game| WasmGame { game: game }).map_err(error)
+    }
+
+    /// Replays a PTN game, failing on the first illegal move
+    #[wasm_bindgen(js_name = fromPtn)]
+    pub fn from_ptn(ptn: &str) -> Result<WasmGame, JsValue> {
+        let ptn = try!(ptn.parse::<Ptn>().map_err(error));
+        ptn.game().map(|game| WasmGame { game: game }).map_err(error)
+    }
+
+    /// Plays a PTN move for the player to move, throwing if it's illegal
+    pub fn play(&mut self, turn: &str) -> Result<(), JsValue> {
+        self.game.play_simple(turn).map(|_| ()).map_err(error)
+    }
+
+    /// Every legal move, in PTN
+    #[wasm_bindgen(js_name = legalMoves)]
+    pub fn legal_moves(&self) -> Vec<String> {
+        if self.game.check_winner().is_some() {
+            return vec![];
+        }
+        self.game.legal_moves().iter().map(|turn| turn.to_string()).collect()
+    }
+
+    #[wasm_bindgen(js_name = isLegal)]
+    pub fn is_legal(&self, turn: &str) -> bool {
+        self.game.clone().play_simple(turn).is_ok() && self.game.check_winner().is_none()
+    }
+
+    #[wasm_bindgen(js_name = toTps)]
+    pub fn to_tps(&self) -> String {
+        self.game.to_tps()
+    }
+
+    #[wasm_bindgen(js_name = toPtn)]
+    pub fn to_ptn(&self) -> String {
+        self.game.as_ptn()
+    }
+
+    /// 1 or 2 once someone has won, otherwise undefined
+    pub fn winner(&self) -> Option<u8> {
+        self.game.check_winner().map(number)
+    }
+
+    /// The PTN result, or an empty string while the game goes on
+    pub fn result(&self) -> String {
+        self.game.result().into()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn size(&self) -> usize {
+        self.game.size()
+    }
+
+    #[wasm_bindgen(getter, js_name = turnNumber)]
+    pub fn turn_number(&self) -> usize {
+        self.game.turn_number()
+    }
+
+    #[wasm_bindgen(getter, js_name = nextPlayer)]
+    pub fn next_player(&self) -> u8 {
+        number(self.game.next_player())
+    }
+}
@@ -1,5 +1,18 @@
+extern crate rustc_serialize;
 extern crate tak;
 
+use std::env;
+use std::sync::atomic::AtomicBool;
+use std::fs;
+use std::fs::File;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process;
+use std::time::Duration;
+use std::time::Instant;
+
+use rustc_serialize::json;
+
 use tak::Game;
 use tak::Board;
 use tak::NaiveBoard;
@@ -9,6 +22,25 @@ use tak::Stone;
 use tak::Point;
 use tak::Player;
 use tak::Ai;
+use tak::Level;
+use tak::Ptn;
+use tak::Direction;
+use tak::Strategy;
+use tak::ai::analysis::Analysis;
+use tak::ai::analysis::Limits;
+use tak::ai::book::Book;
+use tak::ai::eval::Weights;
+use tak::ai::export;
+use tak::ai::moves;
+use tak::ai::puzzle;
+use tak::ai::rng::Rng;
+use tak::ai::search;
+use tak::ai::search::Searcher;
+use tak::ai::search::TranspositionTable;
+use tak::ai::strategy::RandomMover;
+use tak::ai::tournament::Match;
+use tak::ai::tournament::elo_difference;
+use tak::ai::tune;
 
 // First two turns, behavior is hard-coded
 #[test]
@@ -72,3 +104,436 @@ fn blocked_tower() {
     //board.place_piece(&Point::new(1,0), Piece::new(Stone::Standing, Player::Two)).unwrap();
     //assert_eq!(28 + 3 + 3 + 2 + 1, ai.possible_moves(&board).len())
 //}
+
+/// A fresh directory for one test, so runs don't see each other's files
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("tak-{}-{}", name, process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn play_all(moves: Vec<&str>, game: &mut Game) {
+    for m in moves {
+        game.play_simple(m).unwrap();
+    }
+}
+
+#[test]
+fn finds_road_win() {
+    let mut game = Game::new(4);
+    play_all(vec!["b1", "a1", "a2", "b2", "a3", "b3"], &mut game);
+    let ai = Ai::new(Player::One).with_depth(2);
+    assert_eq!(game.predict(ai), Turn::Place { point: Point::new(0, 3), stone: Stone::Flat })
+}
+
+#[test]
+fn blocks_road() {
+    let mut game = Game::new(4);
+    play_all(vec!["b1", "a1", "a2", "c2", "a3", "d3", "c4"], &mut game);
+    let ai = Ai::new(Player::Two).with_depth(2);
+    let turn = game.predict(ai);
+    game.play_turn(turn).unwrap();
+    assert!(game.road_threats(Player::One).is_empty());
+}
+
+#[test]
+fn parallel_finds_road_win() {
+    let mut game = Game::new(4);
+    play_all(vec!["b1", "a1", "a2", "b2", "a3", "b3"], &mut game);
+    let ai = Ai::new(Player::One).with_depth(2).with_threads(4);
+    assert_eq!(game.predict(ai), Turn::Place { point: Point::new(0, 3), stone: Stone::Flat })
+}
+
+#[test]
+fn single_thread_is_deterministic() {
+    let mut game = Game::new(5);
+    play_all(vec!["a1", "e5", "c3", "c2"], &mut game);
+    let first = game.predict(Ai::new(Player::One).with_depth(2));
+    for _ in 0..3 {
+        assert_eq!(game.predict(Ai::new(Player::One).with_depth(2)), first);
+    }
+}
+
+#[test]
+fn follows_book() {
+    let mut book = Book::new();
+    let game = Game::new(5);
+    book.add(game.position_hash(), Turn::Place { point: Point::new(2, 2), stone: Stone::Flat }, 1);
+    let ai = Ai::new(Player::One).with_book(book);
+    assert_eq!(game.predict(ai), Turn::Place { point: Point::new(2, 2), stone: Stone::Flat })
+}
+
+#[test]
+fn book_prefers_weight() {
+    let ptn = "[Size \"5\"]\n1. a1 e5\n2. c3 c2\n3. d3 b3\n";
+    let mut book = Book::new();
+    book.add_game(&ptn.parse::<Ptn>().unwrap().moves, 5, 4).unwrap();
+    let other = "[Size \"5\"]\n1. a1 a5\n";
+    book.add_game(&other.parse::<Ptn>().unwrap().moves, 5, 4).unwrap();
+    book.add_game(&other.parse::<Ptn>().unwrap().moves, 5, 4).unwrap();
+
+    let mut game = Game::new(5);
+    game.play_simple("a1").unwrap();
+    assert_eq!(game.predict(Ai::new(Player::Two).with_book(book.clone())),
+               Turn::Place { point: Point::new(0, 4), stone: Stone::Flat });
+    assert_eq!(book.len(), 4);
+}
+
+#[test]
+fn book_round_trip() {
+    let ptn = "[Size \"5\"]\n1. a1 e5\n2. c3 1a1+1\n";
+    let mut book = Book::new();
+    book.add_game(&ptn.parse::<Ptn>().unwrap().moves, 5, 4).unwrap();
+    assert_eq!(book.to_string().parse::<Book>().unwrap(), book);
+}
+
+#[test]
+fn book_from_directory() {
+    let dir = temp_dir("book");
+    File::create(dir.join("one.ptn")).unwrap()
+        .write_all(b"[Size \"5\"]\n1. a1 e5\n2. c3 c2\n").unwrap();
+    File::create(dir.join("large.ptn")).unwrap()
+        .write_all(b"[Size \"9\"]\n1. a1 b1\n").unwrap();
+    File::create(dir.join("illegal.ptn")).unwrap()
+        .write_all(b"[Size \"4\"]\n1. a1 a1\n").unwrap();
+    File::create(dir.join("notes.txt")).unwrap().write_all(b"ignored").unwrap();
+    let book = Book::from_ptn_dir(&dir, 2).unwrap();
+    assert_eq!(book.len(), 2);
+    book.save(dir.join("book.txt")).unwrap();
+    assert_eq!(Book::load(dir.join("book.txt")).unwrap(), book);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn puzzle_win_in_one() {
+    let mut game = Game::new(4);
+    play_all(vec!["b1", "a1", "a2", "b2", "a3", "b3"], &mut game);
+    assert_eq!(puzzle::solve(&game, 3),
+               Some(vec![Turn::Place { point: Point::new(0, 3), stone: Stone::Flat }]));
+}
+
+#[test]
+fn puzzle_win_in_three() {
+    let mut game = Game::new(4);
+    play_all(vec!["d4", "a2", "b2", "a4", "c1", "a1", "c3", "d3"], &mut game);
+    assert_eq!(puzzle::solve(&game, 1), None);
+
+    let line = puzzle::solve(&game, 3).unwrap();
+    assert_eq!(line.len(), 3);
+    assert_eq!(line[0], Turn::Place { point: Point::new(2, 1), stone: Stone::Flat });
+    for turn in line {
+        game.play_turn(turn).unwrap();
+    }
+    assert_eq!(game.check_road_winner(), Some(Player::One));
+}
+
+#[test]
+fn puzzle_no_win() {
+    let mut game = Game::new(4);
+    play_all(vec!["d4", "a2", "b2", "a4"], &mut game);
+    assert_eq!(puzzle::solve(&game, 3), None);
+}
+
+#[test]
+fn capstone_flattens_wall() {
+    let mut game = Game::new(5);
+    play_all(vec!["a1", "e5", "Cc3", "Sc4"], &mut game);
+    let flatten = Turn::Slide { num_pieces: 1, point: Point::new(2, 2), direction: Direction::Up, drops: vec![1] };
+    assert!(game.legal_moves().contains(&flatten));
+    game.play_turn(flatten).unwrap();
+}
+
+#[test]
+fn move_orders_agree() {
+    let mut game = Game::new(5);
+    play_all(vec!["a1", "e5", "Cc3", "Sc4", "b3", "d3", "c3<", "b2"], &mut game);
+    let mut natural = game.moves(moves::PLACEMENTS_FIRST).map(|t| t.to_string()).collect::<Vec<_>>();
+    let mut captures = game.moves(moves::CAPTURES_FIRST).map(|t| t.to_string()).collect::<Vec<_>>();
+    assert_eq!(natural.len(), game.legal_moves().len());
+    natural.sort();
+    captures.sort();
+    assert_eq!(natural, captures);
+}
+
+#[test]
+fn captures_come_first() {
+    let mut game = Game::new(4);
+    play_all(vec!["a1", "d4", "b1", "c1"], &mut game);
+    let first = game.moves(moves::CAPTURES_FIRST).next().unwrap();
+    assert_eq!(first.to_string(), "1b1>1");
+    assert_eq!(game.moves(moves::CAPTURES_FIRST).take(3).count(), 3);
+}
+
+fn play_out(ai_one: Ai, ai_two: Ai, plies: usize) -> Vec<Turn> {
+    let mut game = Game::new(4);
+    let mut turns = vec![];
+    while turns.len() < plies {
+        let ai = if game.next_player() == Player::One { ai_one.clone() } else { ai_two.clone() };
+        let turn = game.predict(ai);
+        turns.push(turn.clone());
+        if game.play_turn(turn).unwrap().is_some() {
+            break;
+        }
+    }
+    turns
+}
+
+#[test]
+fn seed_reproduces_game() {
+    let one = Ai::new(Player::One).with_level(Level::Beginner).with_seed(7);
+    let two = Ai::new(Player::Two).with_level(Level::Beginner).with_seed(7);
+    assert_eq!(play_out(one.clone(), two.clone(), 12), play_out(one, two, 12));
+    let one = Ai::new(Player::One).with_level(Level::Casual).with_seed(11);
+    let two = Ai::new(Player::Two).with_level(Level::Casual).with_seed(11);
+    assert_eq!(play_out(one.clone(), two.clone(), 10), play_out(one, two, 10));
+}
+
+#[test]
+fn neighbouring_seeds_differ() {
+    for seed in 0..8 {
+        assert!(Rng::new(seed * 2).next_u64() != Rng::new(seed * 2 + 1).next_u64());
+    }
+}
+
+#[test]
+fn seeds_vary_moves() {
+    let mut game = Game::new(5);
+    play_all(vec!["a1", "e5"], &mut game);
+    let mut seen = vec![];
+    for seed in 0..20 {
+        let turn = game.predict(Ai::new(Player::One).with_level(Level::Beginner).with_seed(seed));
+        if !seen.contains(&turn) {
+            seen.push(turn);
+        }
+    }
+    assert!(seen.len() > 1);
+}
+
+#[test]
+fn beginner_still_wins() {
+    let mut game = Game::new(4);
+    play_all(vec!["b1", "a1", "a2", "b2", "a3", "b3"], &mut game);
+    for seed in 0..5 {
+        let ai = Ai::new(Player::One).with_level(Level::Beginner).with_seed(seed);
+        assert_eq!(game.predict(ai), Turn::Place { point: Point::new(0, 3), stone: Stone::Flat });
+    }
+}
+
+#[test]
+fn time_limit() {
+    let mut game = Game::new(5);
+    play_all(vec!["a1", "e5", "c3", "c2"], &mut game);
+    let start = Instant::now();
+    let ai = Ai::new(Player::One).with_depth(20).with_time_limit(Duration::from_millis(200));
+    let turn = game.predict(ai);
+    assert!(start.elapsed() < Duration::from_secs(5));
+    assert!(game.legal_moves().contains(&turn));
+}
+
+#[test]
+fn elo_from_score() {
+    assert_eq!(elo_difference(0.5), 0.0);
+    assert!((elo_difference(0.75) - 190.8).abs() < 0.1);
+    assert!(elo_difference(0.25) < 0.0);
+}
+
+#[test]
+fn tournament() {
+    let strong = Ai::new(Player::One).with_depth(1);
+    let weak = Ai::new(Player::One).with_depth(1).with_margin(10000).with_seed(3);
+    let openings = vec![vec![], vec!["a1".parse::<Turn>().unwrap(), "d4".parse::<Turn>().unwrap()]];
+    let dir = temp_dir("tournament");
+    let result = Match::new(strong, weak, 4, 4)
+                     .with_openings(openings)
+                     .with_max_plies(40)
+                     .with_ptn_dir(dir.clone())
+                     .run()
+                     .unwrap();
+    assert_eq!(result.played(), 4);
+    assert_eq!(result.games.len(), 4);
+    assert!(result.wins > result.losses);
+    for ptn in result.games.iter() {
+        ptn.parse::<Ptn>().unwrap().game().unwrap();
+    }
+    assert!(dir.join("game-004.ptn").exists());
+    assert!(result.to_string().contains("Elo difference"));
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn analysis_top_lines() {
+    let mut game = Game::new(4);
+    play_all(vec!["b1", "a1", "a2", "c2", "a3", "d3", "c4"], &mut game);
+    let analysis = Ai::new(Player::Two).analyze(&game, Limits::new(3, 3));
+    assert_eq!(analysis.lines.len(), 3);
+    assert_eq!(analysis.depth, 3);
+    assert!(analysis.lines[0].score >= analysis.lines[1].score);
+    for line in analysis.lines.iter() {
+        assert_eq!(line.pv[0], line.turn);
+        assert!(line.pv.len() <= 3);
+    }
+    assert_eq!(analysis.lines[0].pv.len(), 3);
+}
+
+#[test]
+fn analysis_finds_win() {
+    let mut game = Game::new(4);
+    play_all(vec!["b1", "a1", "a2", "b2", "a3", "b3"], &mut game);
+    let analysis = Ai::new(Player::One).analyze(&game, Limits::new(3, 1));
+    assert_eq!(analysis.lines[0].turn, Turn::Place { point: Point::new(0, 3), stone: Stone::Flat });
+    assert_eq!(analysis.lines[0].pv.len(), 1);
+}
+
+#[test]
+fn analysis_json() {
+    let mut game = Game::new(4);
+    play_all(vec!["b1", "a1", "a2", "c2"], &mut game);
+    let analysis = Ai::new(Player::One).analyze(&game, Limits::new(2, 2));
+    let encoded = json::encode(&analysis).unwrap();
+    assert!(encoded.contains("\"pv\""));
+    assert_eq!(json::decode::<Analysis>(&encoded).unwrap(), analysis);
+}
+
+#[test]
+fn breakdown_terms() {
+    let mut game = Game::new(4);
+    play_all(vec!["b1", "a1", "a2", "Sc2", "a3"], &mut game);
+    let breakdown = Ai::new(Player::Two).explain(&game);
+    assert_eq!(breakdown.player_one.flats, 3);
+    assert_eq!(breakdown.player_one.road_distance, 1);
+    assert_eq!(breakdown.player_two.flats, 1);
+    assert_eq!(breakdown.player_two.walls, 1);
+    assert_eq!(breakdown.player_one.reserves, 15 - 3);
+    assert_eq!(breakdown.player_two.reserves, 15 - 2);
+    assert_eq!(breakdown.score(), tak::ai::advantage(game.board()));
+    assert!(breakdown.to_string().contains("road_distance"));
+}
+
+#[test]
+fn breakdown_counts_captives() {
+    let mut game = Game::new(4);
+    play_all(vec!["b1", "a1", "a2", "b2", "1a2-1"], &mut game);
+    let breakdown = Ai::new(Player::Two).explain(&game);
+    assert_eq!(breakdown.player_one.stack_control, 1);
+    assert_eq!(breakdown.player_one.flats, 1);
+    let mut game = Game::new(4);
+    play_all(vec!["b1", "a1", "a2", "b2", "1a2>1"], &mut game);
+    let breakdown = Ai::new(Player::Two).explain(&game);
+    assert_eq!(breakdown.player_one.stack_control, 0);
+    assert_eq!(breakdown.player_two.stack_control, 0);
+}
+
+#[test]
+fn weights_round_trip() {
+    let dir = temp_dir("weights");
+    let path = dir.join("weights.json");
+    let weights = Weights { flats: 90, reserves: 5, ..Weights::default() };
+    weights.save(&path).unwrap();
+    assert_eq!(Weights::load(&path).unwrap(), weights);
+    fs::remove_dir_all(&dir).unwrap();
+    let ai = Ai::new(Player::One).with_weights(weights);
+    assert_eq!(ai.explain(&Game::new(4)).weights, weights);
+}
+
+#[test]
+fn tuning_samples_quiet_positions() {
+    let turns = ["b1", "a1", "a2", "b2", "a3", "b3", "a4"].iter()
+                                                          .map(|s| s.parse::<Turn>().unwrap())
+                                                          .collect::<Vec<_>>();
+    // After a3 and b3 both players threaten a road, and a4 ends the game
+    let samples = tune::samples_from_game(&turns, 4, 1.0).unwrap();
+    assert_eq!(samples.len(), 1);
+    assert_eq!(samples[0].player_one.flats, 2);
+}
+
+#[test]
+fn tuning_lowers_error() {
+    let dir = temp_dir("tune");
+    let games = Match::new(Ai::new(Player::One).with_depth(1),
+                           Ai::new(Player::One).with_level(Level::Beginner),
+                           4,
+                           4)
+                    .with_ptn_dir(dir.clone())
+                    .run()
+                    .unwrap();
+    assert_eq!(games.played(), 4);
+    let samples = tune::samples_from_ptn_dir(&dir).unwrap();
+    assert!(!samples.is_empty());
+    let start = Weights::default();
+    let tuned = tune::tune(&samples, start, 10);
+    assert!(tune::error(&samples, &tuned) <= tune::error(&samples, &start));
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn export_rows() {
+    let turns = ["b1", "a1", "Sa2", "1b1+1"].iter()
+                                            .map(|s| s.parse::<Turn>().unwrap())
+                                            .collect::<Vec<_>>();
+    assert!(export::header(3).starts_with("ply,to_move,a1,b1,c1,a2,"));
+    assert!(export::header(3).ends_with(",c3,p1_flats,p1_caps,p2_flats,p2_caps,move,result"));
+    let rows = export::rows(&turns, 3, 0.0).unwrap();
+    assert_eq!(rows.len(), 4);
+    assert_eq!(rows[0], "0,1,,,,,,,,,,10,0,10,0,Fb1,0");
+    assert_eq!(rows[3], "3,2,1,2,,1S,,,,,,8,0,9,0,1b1+1,0");
+}
+
+#[test]
+fn random_mover_plays_legal_moves() {
+    let mut game = Game::new(4);
+    let mut random = RandomMover::new(7);
+    for _ in 0..30 {
+        let turn = game.predict(&mut random);
+        assert!(game.legal_moves().contains(&turn));
+        if game.play_turn(turn).unwrap().is_some() {
+            break;
+        }
+    }
+    let replay = Game::new(4).predict(RandomMover::new(7));
+    assert_eq!(replay, Game::new(4).predict(RandomMover::new(7)));
+}
+
+#[test]
+fn strategies_are_interchangeable() {
+    let mut opponents: Vec<Box<Strategy>> = vec![Box::new(Ai::new(Player::One).with_depth(1)),
+                                                 Box::new(RandomMover::new(1))];
+    let mut game = Game::new(4);
+    play_all(vec!["b1", "a1", "a2", "b2", "a3", "b3"], &mut game);
+    let turn = game.predict(&mut opponents[0]);
+    assert_eq!(turn, Turn::Place { point: Point::new(0, 3), stone: Stone::Flat });
+    assert!(game.legal_moves().contains(&game.predict(&mut opponents[1])));
+}
+
+#[test]
+fn ai_beats_random_mover() {
+    let result = Match::new(Ai::new(Player::One).with_depth(2), RandomMover::new(3), 4, 2)
+                     .run()
+                     .unwrap();
+    assert_eq!(result.wins, 2);
+}
+
+#[test]
+fn transposition_table_is_bounded() {
+    let mut game = Game::new(4);
+    play_all(vec!["b1", "a1", "a2", "c2"], &mut game);
+    let table = TranspositionTable::with_slots(128);
+    let stop = AtomicBool::new(false);
+    let (best, _) = Searcher::new(&table, &stop, 0).iterate(&game, 3);
+    assert!(best.is_some());
+    assert_eq!(table.capacity(), 128);
+    assert!(table.len() <= 128);
+}
+
+#[test]
+fn win_distance_survives_transpositions() {
+    // b2 threatens both b3 and c2, so player one wins on their second move
+    let mut game = Game::new(3);
+    play_all(vec!["a1", "b1", "a2", "c1"], &mut game);
+    for &slots in [16, search::DEFAULT_SLOTS].iter() {
+        let table = TranspositionTable::with_slots(slots);
+        let stop = AtomicBool::new(false);
+        let (_, score) = Searcher::new(&table, &stop, 0).iterate(&game, 4);
+        assert_eq!(score, search::WIN - 3);
+    }
+}
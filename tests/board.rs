@@ -7,6 +7,7 @@ use tak::Stone;
 use tak::Point;
 use tak::Piece;
 use tak::Player;
+use tak::RoadDistance;
 
 #[test]
 fn empty() {
@@ -68,3 +69,33 @@ fn fail_stack() {
         Err(_) => return,
     }
 }
+
+#[test]
+fn road_distance_empty() {
+    let board = NaiveBoard::new(5);
+    assert_eq!(board.road_distance(Player::One),
+               RoadDistance { horizontal: Some(5), vertical: Some(5) });
+}
+
+#[test]
+fn road_distance_counts_own_stones() {
+    let board = "x5/x5/1,1,x,1,2/x5/x5".parse::<NaiveBoard>().unwrap();
+    assert_eq!(board.road_distance(Player::One).horizontal, Some(2));
+    assert_eq!(board.road_distance(Player::One).vertical, Some(4));
+    assert_eq!(board.road_distance(Player::Two).min(), Some(4));
+}
+
+#[test]
+fn road_distance_blocked() {
+    let board = "x,x,2S,x,x/x,x,2C,x,x/x,x,2S,x,x/x,x,2S,x,x/x,x,2S,x,x".parse::<NaiveBoard>().unwrap();
+    assert_eq!(board.road_distance(Player::One).horizontal, None);
+    assert_eq!(board.road_distance(Player::One).vertical, Some(5));
+    assert_eq!(board.road_distance(Player::Two).vertical, Some(4));
+}
+
+#[test]
+fn road_distance_captures_flats() {
+    let board = "x5/x5/2,2,2,2,2/x5/x5".parse::<NaiveBoard>().unwrap();
+    assert_eq!(board.road_distance(Player::One).vertical, Some(5));
+    assert_eq!(board.road_distance(Player::Two).horizontal, Some(0));
+}
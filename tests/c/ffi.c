@@ -0,0 +1,66 @@
+/* Exercises the C interface; built and run by tests/ffi.rs */
+#include <stdio.h>
+#include <string.h>
+
+#include "tak.h"
+
+#define CHECK(condition) \
+    do { \
+        if (!(condition)) { \
+            fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__, __LINE__, #condition); \
+            return 1; \
+        } \
+    } while (0)
+
+int main(void) {
+    const char *moves[] = {"a3", "c3", "a1", "b3", "b1", "a2"};
+    size_t i;
+    char *s;
+    Game *game;
+
+    CHECK(tak_game_new(9) == NULL);
+    CHECK(strstr(tak_last_error(), "Unsupported board size") != NULL);
+
+    game = tak_game_new(3);
+    CHECK(game != NULL);
+    s = tak_game_legal_moves(game);
+    CHECK(strcmp(s, "Fa1 Fa2 Fa3 Fb1 Fb2 Fb3 Fc1 Fc2 Fc3") == 0);
+    tak_string_free(s);
+
+    for (i = 0; i < sizeof(moves) / sizeof(moves[0]); i++) {
+        CHECK(tak_game_play(game, moves[i]) == 0);
+    }
+    CHECK(tak_game_play(game, "a1") == -1);
+    CHECK(tak_last_error() != NULL);
+    CHECK(tak_game_play(game, "zz") == -1);
+    CHECK(tak_game_winner(game) == 0);
+
+    s = tak_game_tps(game);
+    CHECK(strcmp(s, "2,2,1/2,x2/1,1,x 1 4") == 0);
+    tak_string_free(s);
+
+    /* The AI finishes the road */
+    s = tak_ai_move(game, 2);
+    CHECK(s != NULL);
+    CHECK(strcmp(s, "Fc1") == 0);
+    CHECK(tak_game_play(game, s) == 0);
+    tak_string_free(s);
+
+    CHECK(tak_game_winner(game) == 1);
+    s = tak_game_result(game);
+    CHECK(strcmp(s, "R-0") == 0);
+    tak_string_free(s);
+    CHECK(tak_ai_move(game, 2) == NULL);
+    tak_game_free(game);
+
+    game = tak_game_from_tps("x3/x,2,x/1,x2 2 2");
+    CHECK(game != NULL);
+    CHECK(tak_game_play(game, "c3") == 0);
+    tak_game_free(game);
+    CHECK(tak_game_from_tps("nonsense") == NULL);
+
+    tak_game_free(NULL);
+    tak_string_free(NULL);
+    printf("ok\n");
+    return 0;
+}
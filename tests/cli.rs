@@ -0,0 +1,87 @@
+extern crate tak;
+
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::Command;
+use std::process::Output;
+use std::process::Stdio;
+
+use tak::Ptn;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("tak-cli-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn tak(args: &[&str], input: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_tak"))
+                        .args(args)
+                        .stdin(Stdio::piped())
+                        .stdout(Stdio::piped())
+                        .stderr(Stdio::piped())
+                        .spawn()
+                        .unwrap();
+    child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn play_against_human() {
+    let dir = temp_dir("human");
+    let save = dir.join("game.ptn");
+    let output = tak(&["play", "--size", "3", "--vs", "human", "--save", save.to_str().unwrap()],
+                     "a3\nc3\na1\nb3\nb1\na2\nzz\nc2\nundo\nc1\n");
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Invalid move"));
+    assert!(stdout.contains("Result: R-0"));
+
+    let ptn = fs::read_to_string(&save).unwrap().parse::<Ptn>().unwrap();
+    assert_eq!(ptn.tag("Result"), Some("R-0"));
+    assert_eq!(ptn.moves.len(), 7);
+    assert_eq!(ptn.game().unwrap().result(), "R-0");
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn resign_against_ai() {
+    let dir = temp_dir("resign");
+    let save = dir.join("game.ptn");
+    let output = tak(&["play", "--size", "4", "--save", save.to_str().unwrap()], "a1\nresign\n");
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stdout).unwrap().contains("AI plays"));
+
+    let ptn = fs::read_to_string(&save).unwrap().parse::<Ptn>().unwrap();
+    assert_eq!(ptn.tag("Result"), Some("0-1"));
+    assert_eq!(ptn.moves.len(), 2);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn play_rejects_bad_arguments() {
+    let output = tak(&["play", "--size", "9"], "");
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr).unwrap().contains("Invalid size: 9"));
+}
+
+#[test]
+fn check_reports_problem() {
+    let dir = temp_dir("check");
+    let good = dir.join("good.ptn");
+    let bad = dir.join("bad.ptn");
+    fs::write(&good, "[Size \"3\"]\n[Result \"R-0\"]\n1. a3 c3\n2. a1 b3\n3. b1 a2\n4. c1 R-0\n").unwrap();
+    fs::write(&bad, "[Size \"3\"]\n[Result \"R-0\"]\n1. a3 a3\n").unwrap();
+
+    let output = tak(&["check", good.to_str().unwrap()], "");
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stdout).unwrap().contains("7 moves, R-0"));
+
+    let output = tak(&["check", bad.to_str().unwrap()], "");
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr).unwrap().contains("line 3, column 7: Illegal move a3"));
+    fs::remove_dir_all(&dir).unwrap();
+}
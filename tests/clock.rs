@@ -0,0 +1,89 @@
+extern crate tak;
+
+use std::time::Duration;
+
+use tak::Game;
+use tak::Player;
+use tak::Ptn;
+use tak::clock::Clock;
+use tak::clock::ManualTime;
+use tak::clock::TimeControl;
+use tak::clock::TimedGame;
+
+fn secs(n: u64) -> Duration {
+    Duration::from_secs(n)
+}
+
+#[test]
+fn increment_and_switching() {
+    let time = ManualTime::new();
+    let mut clock = Clock::new(TimeControl::new(secs(60), secs(2)), time.clone(), Player::One);
+    time.advance(secs(10));
+    assert_eq!(clock.remaining(Player::One), secs(50));
+    assert_eq!(clock.remaining(Player::Two), secs(60));
+    assert_eq!(clock.press(), secs(10));
+    assert_eq!(clock.running(), Player::Two);
+    assert_eq!(clock.remaining(Player::One), secs(52));
+
+    time.advance(secs(5));
+    clock.press();
+    assert_eq!(clock.remaining(Player::Two), secs(57));
+    assert_eq!(clock.used(), &[secs(10), secs(5)]);
+}
+
+#[test]
+fn delay() {
+    let time = ManualTime::new();
+    let control = TimeControl::new(secs(60), secs(0)).with_delay(secs(3));
+    let mut clock = Clock::new(control, time.clone(), Player::One);
+    time.advance(secs(2));
+    assert_eq!(clock.remaining(Player::One), secs(60));
+    time.advance(secs(3));
+    assert_eq!(clock.remaining(Player::One), secs(58));
+    clock.press();
+    assert_eq!(clock.remaining(Player::One), secs(58));
+}
+
+#[test]
+fn forfeit_on_time() {
+    let time = ManualTime::new();
+    let mut game = TimedGame::new(Game::new(5), TimeControl::new(secs(30), secs(0)), time.clone());
+    game.play("a1").unwrap();
+    time.advance(secs(29));
+    game.play("e5").unwrap();
+    assert_eq!(game.check_time(), None);
+
+    time.advance(secs(5));
+    game.play("b1").unwrap();
+    time.advance(secs(2));
+    assert_eq!(game.play("d5"), Err("Player 2 ran out of time".into()));
+    assert_eq!(game.check_time(), Some(Player::Two));
+    assert_eq!(game.result(), "1-0");
+    assert_eq!(game.play("d5"), Err("The game is over".into()));
+    assert_eq!(game.game().turn_number(), 3);
+}
+
+#[test]
+fn illegal_moves_keep_clock_running() {
+    let time = ManualTime::new();
+    let mut game = TimedGame::new(Game::new(5), TimeControl::new(secs(30), secs(0)), time.clone());
+    assert!(game.play("Sa1").is_err());
+    assert_eq!(game.clock().running(), Player::One);
+    assert!(game.clock().used().is_empty());
+}
+
+#[test]
+fn times_in_ptn() {
+    let time = ManualTime::new();
+    let mut game = TimedGame::new(Game::new(5), TimeControl::new(secs(600), secs(5)), time.clone());
+    time.advance(Duration::from_millis(1250));
+    game.play("a1").unwrap();
+    time.advance(secs(3));
+    game.play("e5").unwrap();
+    time.advance(Duration::from_millis(40));
+    game.play("b1").unwrap();
+
+    let ptn = game.as_ptn_with_players("alice", "bob");
+    assert!(ptn.ends_with("1. Fa1 {1.250s} Fe5 {3.000s}\n2. Fb1 {0.040s}"), "{}", ptn);
+    assert_eq!(ptn.parse::<Ptn>().unwrap().moves.len(), 3);
+}
@@ -0,0 +1,38 @@
+// Builds tests/c/ffi.c against the static library and runs it
+#![cfg(unix)]
+
+use std::env;
+use std::path::PathBuf;
+use std::process::Command;
+
+#[test]
+fn c_program() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    // Test binaries live in target/<profile>/deps. `cargo test` leaves the
+    // static library there too, and `cargo build` copies it up a level.
+    let exe = env::current_exe().unwrap();
+    let deps = exe.parent().unwrap();
+    let lib = [deps.join("libtak.a"), deps.parent().unwrap().join("libtak.a")]
+                  .iter()
+                  .find(|lib| lib.exists())
+                  .cloned()
+                  .expect("libtak.a wasn't built");
+    let program = deps.join(format!("ffi-c-{}", std::process::id()));
+
+    let compiler = env::var("CC").unwrap_or("cc".into());
+    let status = Command::new(compiler)
+                     .arg(root.join("tests/c/ffi.c"))
+                     .arg("-I")
+                     .arg(root.join("include"))
+                     .arg(&lib)
+                     .args(&["-lpthread", "-ldl", "-lm", "-o"])
+                     .arg(&program)
+                     .status()
+                     .unwrap();
+    assert!(status.success());
+
+    let output = Command::new(&program).output().unwrap();
+    let _ = std::fs::remove_file(&program);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "ok\n");
+}
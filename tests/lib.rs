@@ -1,7 +1,14 @@
+extern crate rustc_serialize;
 extern crate tak;
 
+use rustc_serialize::json;
+
+use tak::Board;
 use tak::Game;
 use tak::Player;
+use tak::Point;
+use tak::Stone;
+use tak::Turn;
 
 fn play_no_win(moves: Vec<&str>, game: &mut Game) -> () {
     for str in moves {
@@ -270,3 +277,137 @@ fn example1() {
     play_no_win(m, &mut game);
     assert_eq!(game.play("a4+", Player::Two, None).unwrap(), Some(Player::Two));
 }
+
+#[test]
+fn road_threats() {
+    let mut game = Game::new(4);
+    play_no_win(vec!["b1", "a1", "a2", "b2", "a3"], &mut game);
+    assert_eq!(game.road_threats(Player::One), vec![Turn::Place { point: Point::new(0, 3), stone: Stone::Flat }]);
+    assert!(game.is_tak());
+    assert!(game.road_threats(Player::Two).is_empty());
+}
+
+#[test]
+fn no_threats_in_opening() {
+    let mut game = Game::new(4);
+    play_no_win(vec!["a1"], &mut game);
+    assert!(!game.is_tak());
+}
+
+#[test]
+fn slide_threat() {
+    let mut game = Game::new(4);
+    play_no_win(vec!["d4", "a1", "b2", "c4", "c1", "d3", "d1", "c3"], &mut game);
+    let threats = game.road_threats(Player::One);
+    assert_eq!(threats.len(), 2);
+    assert!(threats.contains(&Turn::Place { point: Point::new(1, 0), stone: Stone::Flat }));
+    assert!(threats.iter().any(|t| t.to_string() == "1b2-1"));
+}
+
+#[test]
+fn ptn_marks_tak() {
+    let mut game = Game::new(4);
+    play_no_win(vec!["b1", "a1", "a2", "b2", "a3"], &mut game);
+    assert!(game.as_ptn().ends_with("1. Fb1 Fa1\n2. Fa2 Fb2\n3. Fa3'"));
+}
+
+#[test]
+fn failed_slide_leaves_board() {
+    let mut game = Game::new(5);
+    game.play_simple("a1").unwrap();
+    game.play_simple("e5").unwrap();
+    game.play_simple("c3").unwrap();
+    let before = game.to_string();
+    assert!(game.play_simple("1a1<1").is_err());
+    assert!(game.play_simple("2a1>11").is_err());
+    assert_eq!(game.to_string(), before);
+    game.play_simple("1a1>1").unwrap();
+    assert!(game.as_ptn().contains("1a1>1"));
+}
+
+#[test]
+fn tps_round_trip() {
+    let mut game = Game::new(5);
+    play_no_win(vec!["a1", "e5", "b1", "Cc3", "1b1<1"], &mut game);
+    let tps = game.to_tps();
+    assert_eq!(tps, "x4,1/x5/x2,2C,x2/x5/21,x4 2 3");
+
+    let copy = Game::from_tps(&tps).unwrap();
+    assert_eq!(copy.to_string(), game.to_string());
+    assert_eq!(copy.turn_number(), 5);
+    assert_eq!(copy.next_player(), Player::Two);
+    assert_eq!(copy.position_hash(), game.position_hash());
+    assert_eq!(copy.board().count().p1_cap, 0);
+    assert_eq!(copy.board().count().p2_cap, 1);
+}
+
+#[test]
+fn invalid_tps() {
+    assert!(Game::from_tps("x4/x4/x4/x4 1").is_err());
+    assert!(Game::from_tps("x4/x4/x4/x3 1 1").is_err());
+    assert!(Game::from_tps("x4/x4/x4/x4 3 1").is_err());
+    assert!(Game::from_tps("x4/x4/x4/x4 1 0").is_err());
+    assert!(Game::from_tps("x4/x4/x4/1S2,x3 1 2").is_err());
+}
+
+#[test]
+fn tps_game_continues() {
+    let mut game = Game::from_tps("x3/x,2,x/1,x2 2 2").unwrap();
+    game.play_simple("c3").unwrap();
+    game.play_simple("a2").unwrap();
+    let ptn = game.as_ptn();
+    assert!(ptn.contains("[TPS \"x3/x,2,x/1,x2 2 2\"]"));
+    assert!(ptn.ends_with("2. -- Fc3\n3. Fa2'"));
+
+    let replayed = ptn.parse::<tak::Ptn>().unwrap().game().unwrap();
+    assert_eq!(replayed.to_tps(), game.to_tps());
+}
+
+#[test]
+fn undo_restores_position() {
+    let mut game = Game::new(5);
+    play_no_win(vec!["a1", "e5", "b1", "c3"], &mut game);
+    let before = game.to_tps();
+    game.play_simple("1b1<1").unwrap();
+    assert_eq!(game.undo().map(|turn| turn.to_string()), Some("1b1<1".to_string()));
+    assert_eq!(game.to_tps(), before);
+    assert_eq!(game.history().len(), 4);
+    assert_eq!(game.board().count().p1_flat, 2);
+
+    let mut empty = Game::new(3);
+    assert_eq!(empty.undo(), None);
+}
+
+#[test]
+fn observers_see_events() {
+    use std::sync::Arc;
+    use std::sync::Mutex;
+    use tak::observer::Event;
+
+    let seen = Arc::new(Mutex::new(vec![]));
+    let mut game = Game::new(3);
+    {
+        let seen = seen.clone();
+        game.observe(move |event, game| seen.lock().unwrap().push((event.clone(), game.turn_number())));
+    }
+    play_no_win(vec!["a3", "c3", "a1", "b3", "b1"], &mut game);
+    assert_eq!(seen.lock().unwrap().last(), Some(&(Event::Tak(Player::One), 5)));
+
+    let mut copy = game.clone();
+    copy.play_simple("a2").unwrap();
+    assert_eq!(seen.lock().unwrap().len(), 6);
+
+    assert_eq!(game.undo().map(|turn| turn.to_string()), Some("Fb1".to_string()));
+    assert_eq!(seen.lock().unwrap().last(), Some(&(Event::Undone("b1".parse().unwrap()), 4)));
+    game.play_simple("b1").unwrap();
+    game.play_simple("a2").unwrap();
+    assert_eq!(game.play_simple("c1").unwrap(), Some(Player::One));
+
+    let seen = seen.lock().unwrap();
+    assert_eq!(seen[0], (Event::Moved("a3".parse().unwrap()), 1));
+    assert_eq!(&seen[seen.len() - 2..],
+               &[(Event::Moved("c1".parse().unwrap()), 7), (Event::Over("R-0".into()), 7)]);
+
+    let decoded = json::decode::<Game>(&json::encode(&game).unwrap()).unwrap();
+    assert_eq!(decoded.to_tps(), game.to_tps());
+}
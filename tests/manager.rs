@@ -0,0 +1,80 @@
+extern crate tak;
+
+use std::sync::Arc;
+use std::thread;
+
+use tak::Player;
+use tak::manager::Event;
+use tak::manager::GameManager;
+
+#[test]
+fn seats_and_turns() {
+    let manager = GameManager::new();
+    let id = manager.create(4, "alice", "bob").unwrap();
+    assert_eq!(manager.seat(id, "alice"), Some(Player::One));
+    assert_eq!(manager.seat(id, "bob"), Some(Player::Two));
+    assert_eq!(manager.seat(id, "carol"), None);
+
+    assert_eq!(manager.play(id, "bob", "a1"), Err("Not your turn".into()));
+    assert!(manager.play(id, "carol", "a1").is_err());
+    assert!(manager.play(id + 1, "alice", "a1").is_err());
+    assert!(manager.play(id, "alice", "zz").is_err());
+    assert_eq!(manager.play(id, "alice", "a1"), Ok(None));
+    assert_eq!(manager.play(id, "alice", "d4"), Err("Not your turn".into()));
+    assert_eq!(manager.play(id, "bob", "d4"), Ok(None));
+    assert_eq!(manager.game(id).unwrap().turn_number(), 2);
+
+    assert!(manager.create(4, "alice", "alice").is_err());
+    assert!(manager.create(9, "alice", "bob").is_err());
+}
+
+#[test]
+fn events_and_results() {
+    let manager = GameManager::new();
+    let events = manager.subscribe();
+    let id = manager.create(3, "alice", "bob").unwrap();
+    for (player, turn) in vec![("alice", "a3"), ("bob", "c3"), ("alice", "a1"), ("bob", "b3"),
+                               ("alice", "b1"), ("bob", "a2")] {
+        assert_eq!(manager.play(id, player, turn), Ok(None));
+    }
+    assert_eq!(manager.play(id, "alice", "c1"), Ok(Some(Player::One)));
+    assert_eq!(manager.result(id), Some("R-0".into()));
+    assert_eq!(manager.play(id, "bob", "c2"), Err("The game is over".into()));
+
+    let events = events.try_iter().collect::<Vec<_>>();
+    assert_eq!(events.len(), 9);
+    assert_eq!(events[0], Event::Started { game: id, size: 3, player_one: "alice".into(), player_two: "bob".into() });
+    assert_eq!(events[7], Event::Moved { game: id, player: Player::One, turn: "c1".parse().unwrap() });
+    assert_eq!(events[8], Event::Over { game: id, result: "R-0".into() });
+
+    let other = manager.create(5, "carol", "alice").unwrap();
+    assert_eq!(manager.games_of("alice"), vec![id, other]);
+    manager.resign(other, "alice").unwrap();
+    assert_eq!(manager.result(other), Some("1-0".into()));
+    assert!(manager.resign(other, "carol").is_err());
+    assert!(manager.remove(id).is_some());
+    assert_eq!(manager.games_of("alice"), vec![other]);
+}
+
+#[test]
+fn concurrent_games() {
+    let manager = Arc::new(GameManager::new());
+    let threads = (0..8).map(|i| {
+        let manager = manager.clone();
+        thread::spawn(move || {
+            let (one, two) = (format!("one{}", i), format!("two{}", i));
+            let id = manager.create(5, &one, &two).unwrap();
+            for (player, turn) in vec![(&one, "a1"), (&two, "e5"), (&one, "b1"), (&two, "d5")] {
+                manager.play(id, player, turn).unwrap();
+            }
+            id
+        })
+    }).collect::<Vec<_>>();
+    let mut ids = threads.into_iter().map(|t| t.join().unwrap()).collect::<Vec<_>>();
+    ids.sort();
+    ids.dedup();
+    assert_eq!(ids.len(), 8);
+    for id in ids {
+        assert_eq!(manager.game(id).unwrap().turn_number(), 4);
+    }
+}
@@ -0,0 +1,54 @@
+extern crate tak;
+
+use tak::Game;
+use tak::ai::perft::perft;
+use tak::ai::perft::perft_divide;
+use tak::ai::perft::reference;
+
+fn check(size: usize, depth: usize) {
+    let counts = reference(size).unwrap();
+    let game = Game::new(size);
+    for d in 0..depth + 1 {
+        assert_eq!(perft(&game, d), counts[d], "size {} depth {}", size, d);
+    }
+}
+
+#[test]
+fn perft_3x3() {
+    check(3, 5);
+}
+
+#[test]
+fn perft_4x4() {
+    check(4, 4);
+}
+
+#[test]
+fn perft_5x5() {
+    check(5, 3);
+}
+
+#[test]
+fn perft_6x6() {
+    check(6, 3);
+}
+
+#[test]
+fn perft_7x7() {
+    check(7, 3);
+}
+
+#[test]
+fn perft_8x8() {
+    check(8, 3);
+}
+
+#[test]
+fn divide_sums_to_perft() {
+    let mut game = Game::new(5);
+    game.play_simple("a1").unwrap();
+    game.play_simple("e5").unwrap();
+    let divide = perft_divide(&game, 2);
+    assert_eq!(divide.len(), 23 * 3 + 2);
+    assert_eq!(divide.iter().fold(0, |sum, &(_, x)| sum + x), perft(&game, 2));
+}
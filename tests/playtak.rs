@@ -0,0 +1,148 @@
+extern crate tak;
+
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Write;
+use std::net::TcpListener;
+use std::thread;
+
+use tak::Game;
+use tak::Player;
+use tak::Strategy;
+use tak::Turn;
+use tak::ai::strategy::RandomMover;
+use tak::playtak::Bot;
+use tak::playtak::Command;
+use tak::playtak::GameStart;
+use tak::playtak::Message;
+use tak::playtak::Seek;
+use tak::playtak::encode_turn;
+use tak::playtak::parse_turn;
+
+#[test]
+fn turns() {
+    let cases = [("a1", "P A1"), ("Sb2", "P B2 W"), ("Cc3", "P C3 C"),
+                 ("3a1>12", "M A1 C1 1 2"), ("1c3-1", "M C3 C2 1"),
+                 ("2d4<11", "M D4 B4 1 1"), ("1e1+1", "M E1 E2 1")];
+    for &(ptn, playtak) in cases.iter() {
+        let turn = ptn.parse::<Turn>().unwrap();
+        assert_eq!(encode_turn(&turn), playtak);
+        assert_eq!(parse_turn(playtak).unwrap(), turn);
+    }
+}
+
+#[test]
+fn invalid_turns() {
+    assert!(parse_turn("P A9").is_err());
+    assert!(parse_turn("P A1 X").is_err());
+    assert!(parse_turn("M A1 B2 1").is_err());
+    assert!(parse_turn("M A1 C1 1").is_err());
+    assert!(parse_turn("M A1 B1 0").is_err());
+    assert!(parse_turn("Q A1").is_err());
+}
+
+#[test]
+fn server_messages() {
+    let seek = Message::SeekNew {
+        id: 12,
+        name: "alice".into(),
+        seek: Seek { size: 6, time: 900, increment: 20, color: Some(Player::Two) },
+    };
+    let start = Message::GameStart(GameStart {
+        id: 7,
+        size: 5,
+        white: "alice".into(),
+        black: "bob".into(),
+        color: Player::Two,
+        time: 180,
+    });
+    let messages = [("Welcome bob!", Message::Welcome("bob".into())),
+                    ("Seek new 12 alice 6 900 20 B", seek),
+                    ("Seek remove 12", Message::SeekRemove { id: 12 }),
+                    ("Game Start 7 5 alice vs bob black 180", start),
+                    ("Game#7 P A1 C", Message::Move { game: 7, turn: "Ca1".parse().unwrap() }),
+                    ("Game#7 Time 170 175", Message::Time { game: 7, white: 170, black: 175 }),
+                    ("Game#7 Over R-0", Message::Over { game: 7, result: "R-0".into() }),
+                    ("NOK", Message::Nok)];
+    for &(ref line, ref message) in messages.iter() {
+        assert_eq!(&line.parse::<Message>().unwrap(), message);
+        assert_eq!(&message.to_string(), line);
+    }
+    // Newer servers add fields, and there are lines the bot ignores
+    assert_eq!("Seek new 3 carol 5 600 0 A 0 21 1 0 0".parse::<Message>().unwrap().to_string(),
+               "Seek new 3 carol 5 600 0 A");
+    assert_eq!("Shout <carol> hi".parse::<Message>().unwrap(),
+               Message::Other("Shout <carol> hi".into()));
+    assert!("Game#7 P Z9".parse::<Message>().is_err());
+}
+
+#[test]
+fn client_commands() {
+    let commands = [("Login bot secret", Command::Login { name: "bot".into(), password: "secret".into() }),
+                    ("Login Guest", Command::Login { name: "Guest".into(), password: "".into() }),
+                    ("Seek 5 600 10 W", Command::Seek(Seek { size: 5, time: 600, increment: 10, color: Some(Player::One) })),
+                    ("Seek 4 60 0", Command::Seek(Seek { size: 4, time: 60, increment: 0, color: None })),
+                    ("Accept 3", Command::Accept(3)),
+                    ("Game#3 M B2 B4 2 1", Command::Move { game: 3, turn: "3b2+21".parse().unwrap() }),
+                    ("Game#3 Resign", Command::Resign(3)),
+                    ("quit", Command::Quit)];
+    for &(ref line, ref command) in commands.iter() {
+        assert_eq!(&line.parse::<Command>().unwrap(), command);
+        assert_eq!(&command.to_string(), line);
+    }
+}
+
+#[test]
+fn bot_plays_mock_server() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut writer = stream;
+        let mut read = || {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            line.parse::<Command>().unwrap()
+        };
+
+        writeln!(writer, "Welcome!\nLogin or Register").unwrap();
+        match read() {
+            Command::Client(_) => {}
+            other => panic!("{:?}", other),
+        }
+        assert_eq!(read(), Command::Login { name: "bot".into(), password: "secret".into() });
+        writeln!(writer, "Welcome bot!").unwrap();
+        assert_eq!(read(), Command::Seek(Seek { size: 3, time: 60, increment: 0, color: None }));
+        writeln!(writer, "Seek new 1 bot 3 60 0 A").unwrap();
+        writeln!(writer, "Game Start 1 3 bot vs mock white 60").unwrap();
+
+        // Play randomly against the bot until the game ends
+        let mut game = Game::new(3);
+        let mut mover = RandomMover::new(5);
+        while game.check_winner().is_none() {
+            let turn = if game.next_player() == Player::One {
+                match read() {
+                    Command::Move { game: 1, turn } => turn,
+                    other => panic!("{:?}", other),
+                }
+            } else {
+                let turn = mover.choose(&game);
+                writeln!(writer, "{}", Message::Move { game: 1, turn: turn.clone() }).unwrap();
+                writeln!(writer, "Game#1 Time 59 58").unwrap();
+                turn
+            };
+            game.play_turn(turn).unwrap();
+        }
+        writeln!(writer, "Game#1 Over {}", game.result()).unwrap();
+        game
+    });
+
+    let mut bot = Bot::new("bot", "secret", RandomMover::new(9))
+                      .with_seek(Seek { size: 3, time: 60, increment: 0, color: None });
+    let played = bot.connect(addr).unwrap();
+    let game = server.join().unwrap();
+    assert_eq!(played.id, 1);
+    assert_eq!(played.result, game.result());
+    assert_eq!(played.game.to_tps(), game.to_tps());
+}
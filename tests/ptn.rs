@@ -0,0 +1,90 @@
+extern crate tak;
+
+use tak::Ptn;
+use tak::Player;
+use tak::Point;
+use tak::Stone;
+use tak::Turn;
+
+static GAME: &'static str = "[Site \"PlayTak.com\"]\n\
+                             [Player1 \"alice\"]\n\
+                             [Player2 \"bob\"]\n\
+                             [Size \"4\"]\n\
+                             [Result \"R-0\"]\n\
+                             \n\
+                             1. b1 a1 {opening}\n\
+                             2. a2 b2\n\
+                             3. a3' b3!\n\
+                             4. a4 R-0\n";
+
+#[test]
+fn tags() {
+    let ptn = GAME.parse::<Ptn>().unwrap();
+    assert_eq!(ptn.tag("Player1"), Some("alice"));
+    assert_eq!(ptn.tag("Result"), Some("R-0"));
+    assert_eq!(ptn.size(), Some(4));
+}
+
+#[test]
+fn moves() {
+    let ptn = GAME.parse::<Ptn>().unwrap();
+    assert_eq!(ptn.moves.len(), 7);
+    assert_eq!(ptn.moves[6], Turn::Place { point: Point::new(0, 3), stone: Stone::Flat });
+}
+
+#[test]
+fn replay() {
+    let game = GAME.parse::<Ptn>().unwrap().game().unwrap();
+    assert_eq!(game.turn_number(), 7);
+    assert_eq!(game.check_winner(), Some(Player::One));
+}
+
+#[test]
+fn bad_move() {
+    assert!("[Size \"5\"]\n1. a1 zz9".parse::<Ptn>().is_err());
+}
+
+#[test]
+fn illegal_move() {
+    let ptn = "[Size \"5\"]\n1. a1 a1".parse::<Ptn>().unwrap();
+    assert!(ptn.game().is_err());
+}
+
+#[test]
+fn check_accepts_game() {
+    let game = tak::ptn::check(GAME).unwrap();
+    assert_eq!(game.result(), "R-0");
+    let resigned = "[Size \"4\"]\n[Result \"0-1\"]\n1. a1 d4\n2. a2 0-1\n";
+    assert_eq!(tak::ptn::check(resigned).unwrap().turn_number(), 3);
+}
+
+#[test]
+fn check_finds_illegal_move() {
+    let text = "[Size \"4\"]\n[Result \"R-0\"]\n1. a1 d4\n2. a2  d4 {taken}\n";
+    let problem = tak::ptn::check(text).unwrap_err();
+    assert_eq!((problem.line, problem.column), (4, 8));
+    assert!(problem.message.starts_with("Illegal move d4"), "{}", problem);
+
+    let bad = "[Size \"4\"]\n[Result \"R-0\"]\n{a comment\nover lines} 1. b1 a1 2. a2 q7\n";
+    let problem = tak::ptn::check(bad).unwrap_err();
+    assert_eq!((problem.line, problem.column), (4, 28));
+    assert_eq!(problem.message, "Invalid move: q7");
+}
+
+#[test]
+fn check_finds_wrong_result() {
+    let flat = GAME.replace("[Result \"R-0\"]", "[Result \"F-0\"]");
+    let problem = tak::ptn::check(&flat).unwrap_err();
+    assert_eq!((problem.line, problem.column), (5, 1));
+    assert_eq!(problem.message, "Result is F-0, but R-0");
+
+    let early = "[Size \"4\"]\n[Result \"R-0\"]\n1. a1 d4\n";
+    assert_eq!(tak::ptn::check(early).unwrap_err().message, "Result is R-0, but the game isn't over");
+
+    let late = GAME.replace("4. a4 R-0", "4. a4 b4");
+    let problem = tak::ptn::check(&late).unwrap_err();
+    assert_eq!((problem.line, problem.column), (10, 7));
+
+    let token = GAME.replace("4. a4 R-0", "4. a4 0-R");
+    assert_eq!(tak::ptn::check(&token).unwrap_err().message, "0-R doesn't match the Result tag R-0");
+}
@@ -0,0 +1,101 @@
+# Exercises the Python bindings; run with `pytest` once the wheel is installed
+import pytest
+
+import tak
+
+
+def test_points():
+    point = tak.Point.parse("c2")
+    assert (point.x, point.y) == (2, 1)
+    assert point == tak.Point(2, 1)
+    assert str(point) == "c2"
+    assert len({point, tak.Point(2, 1)}) == 1
+    with pytest.raises(ValueError):
+        tak.Point.parse("z9")
+
+
+def test_turns():
+    place = tak.Turn.parse("Sb3")
+    assert place.kind == "place"
+    assert place.point == tak.Point.parse("b3")
+    assert place.stone == "S"
+    assert place.direction is None
+
+    slide = tak.Turn.parse("3a1>12")
+    assert slide.kind == "slide"
+    assert slide.stone is None
+    assert slide.direction == ">"
+    assert slide.drops == [1, 2]
+    assert str(slide) == "3a1>12"
+    with pytest.raises(ValueError):
+        tak.Turn.parse("nonsense")
+
+
+def test_play_and_undo():
+    game = tak.Game(3)
+    assert game.size == 3
+    assert game.next_player == 1
+    assert [str(turn) for turn in game.legal_moves()] == \
+        ["Fa1", "Fa2", "Fa3", "Fb1", "Fb2", "Fb3", "Fc1", "Fc2", "Fc3"]
+
+    assert game.play("a1") is None
+    assert game.play(tak.Turn.parse("c3")) is None
+    assert game.turn_number == 2
+    assert [str(turn) for turn in game.history()] == ["Fa1", "Fc3"]
+    assert not game.is_legal("a1")
+    with pytest.raises(ValueError):
+        game.play("a1")
+
+    assert str(game.undo()) == "Fc3"
+    assert game.is_legal("c3")
+    assert game.turn_number == 1
+
+
+def test_board():
+    # Both openings place the opponent's stone
+    game = tak.Game(5)
+    game.play("a1")
+    game.play("b1")
+    game.play("b1<")
+    game.play("Cc1")
+    assert game.stack("a1") == [(2, "F"), (1, "F")]
+    assert game.top(tak.Point.parse("a1")) == (1, "F")
+    assert game.top("c1") == (2, "C")
+    assert game.top("e5") is None
+    assert game.stack("b1") == []
+
+
+def test_tps_and_ptn():
+    game = tak.Game.from_tps("x3/x,2,x/1,x2 2 2")
+    assert game.next_player == 2
+    assert game.to_tps() == "x3/x,2,x/1,x2 2 2"
+
+    game = tak.Game(5)
+    for turn in ["a1", "e5", "b1", "e4"]:
+        game.play(turn)
+    ptn = game.to_ptn("Alice", "Bob")
+    assert '[Player1 "Alice"]' in ptn
+    replayed = tak.Game.from_ptn(ptn)
+    assert replayed.to_tps() == game.to_tps()
+
+    with pytest.raises(ValueError):
+        tak.Game.from_tps("nonsense")
+    with pytest.raises(ValueError):
+        tak.Game(9)
+
+
+def test_ai_finishes_road():
+    game = tak.Game(3)
+    for turn in ["a3", "c3", "a1", "b3", "b1"]:
+        game.play(turn)
+    assert game.is_tak()
+    game.play("a2")
+
+    turn = tak.Ai(depth=2).best_move(game)
+    assert str(turn) == "Fc1"
+    assert game.play(turn) == 1
+    assert game.winner() == 1
+    assert game.result() == "R-0"
+    assert game.legal_moves() == []
+    with pytest.raises(ValueError):
+        tak.Ai().best_move(game)
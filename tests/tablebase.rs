@@ -0,0 +1,101 @@
+extern crate tak;
+
+use std::env;
+use std::fs;
+
+use tak::Ai;
+use tak::Game;
+use tak::Player;
+use tak::ai::analysis::Limits;
+use tak::ai::search::WIN;
+use tak::ai::tablebase::Tablebase;
+use tak::ai::tablebase::TablebaseLimits;
+use tak::ai::tablebase::Value;
+
+fn position(moves: &[&str]) -> Game {
+    let mut game = Game::new(3);
+    for m in moves {
+        game.play_simple(m).unwrap();
+    }
+    game
+}
+
+// Player one has b1 and a2, and b2 threatens both b3 and c2
+fn setup() -> Game {
+    position(&["a1", "b1", "a2", "c1"])
+}
+
+fn limits() -> TablebaseLimits {
+    let mut limits = TablebaseLimits::new(9, 10);
+    limits.max_positions = 1000;
+    limits
+}
+
+fn solved() -> Tablebase {
+    Tablebase::generate(3, &[setup()], &limits())
+}
+
+#[test]
+fn win_in_one() {
+    let tablebase = solved();
+    assert_eq!(tablebase.probe(&setup()), Some(Value::Win(3)));
+    let threat = position(&["a1", "b1", "a2", "c1", "b2", "b3"]);
+    assert_eq!(tablebase.probe(&threat), Some(Value::Win(1)));
+}
+
+#[test]
+fn double_threat_is_lost() {
+    let tablebase = solved();
+    let double = position(&["a1", "b1", "a2", "c1", "b2"]);
+    assert_eq!(tablebase.probe(&double), Some(Value::Loss(2)));
+}
+
+#[test]
+fn other_sizes_are_not_probed() {
+    let tablebase = solved();
+    assert_eq!(tablebase.size(), 3);
+    assert_eq!(tablebase.probe(&Game::new(4)), None);
+}
+
+#[test]
+fn bytes_round_trip() {
+    let tablebase = solved();
+    assert!(tablebase.len() > 0);
+    let bytes = tablebase.to_bytes();
+    assert_eq!(Tablebase::from_bytes(&bytes).unwrap(), tablebase);
+
+    let dir = env::temp_dir().join(format!("tak-tablebase-test-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    tablebase.save(dir.join("3x3.tb")).unwrap();
+    assert_eq!(Tablebase::load(dir.join("3x3.tb")).unwrap(), tablebase);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn rejects_bad_files() {
+    let bytes = solved().to_bytes();
+    assert!(Tablebase::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    assert!(Tablebase::from_bytes(&bytes[..4]).is_err());
+    let mut magic = bytes.clone();
+    magic[0] = b'X';
+    assert!(Tablebase::from_bytes(&magic).is_err());
+    let mut version = bytes.clone();
+    version[5] = 99;
+    assert!(Tablebase::from_bytes(&version).is_err());
+}
+
+#[test]
+fn search_uses_tablebase() {
+    let game = setup();
+    let ai = Ai::new(Player::One).with_depth(1).with_tablebase(solved());
+    let analysis = ai.analyze(&game, Limits::new(1, 1));
+    assert_eq!(analysis.lines[0].score, WIN - 3);
+
+    let turn = ai.best_move(&game);
+    let mut next = game.clone();
+    next.play_turn(turn).unwrap();
+    assert_eq!(solved().probe(&next), Some(Value::Loss(2)));
+
+    let plain = Ai::new(Player::One).with_depth(1).analyze(&game, Limits::new(1, 1));
+    assert!(plain.lines[0].score < WIN - 100);
+}
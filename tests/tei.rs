@@ -0,0 +1,86 @@
+extern crate tak;
+
+use std::sync::mpsc;
+use std::sync::mpsc::Receiver;
+use std::time::Duration;
+
+use tak::Game;
+use tak::tei::Tei;
+
+fn engine() -> (Tei, Receiver<String>) {
+    let (sender, receiver) = mpsc::channel();
+    (Tei::new(sender), receiver)
+}
+
+// Every line up to and including the one starting with prefix
+fn until(receiver: &Receiver<String>, prefix: &str) -> Vec<String> {
+    let mut lines = vec![];
+    loop {
+        let line = receiver.recv_timeout(Duration::from_secs(30)).unwrap();
+        let done = line.starts_with(prefix);
+        lines.push(line);
+        if done {
+            return lines;
+        }
+    }
+}
+
+#[test]
+fn handshake() {
+    let (mut tei, receiver) = engine();
+    assert!(tei.handle("tei"));
+    let lines = until(&receiver, "teiok");
+    assert!(lines[0].starts_with("id name "));
+    assert!(lines.iter().any(|line| line.starts_with("option name Size")));
+    tei.handle("isready");
+    assert_eq!(receiver.recv().unwrap(), "readyok");
+    assert!(!tei.handle("quit"));
+}
+
+#[test]
+fn finds_road() {
+    let (mut tei, receiver) = engine();
+    tei.handle("setoption name Size value 4");
+    tei.handle("teinew");
+    tei.handle("position startpos moves d4 a1 a2 d3 a3 d2");
+    tei.handle("go depth 3");
+    let lines = until(&receiver, "bestmove");
+    assert_eq!(lines.last().unwrap(), "bestmove a4");
+    let info = &lines[lines.len() - 2];
+    assert!(info.starts_with("info depth 1 score mate 1 "), "{}", info);
+    assert!(info.ends_with(" pv a4"), "{}", info);
+}
+
+#[test]
+fn position_from_tps() {
+    let (mut tei, receiver) = engine();
+    tei.handle("position tps x3/x,2,x/1,x2 2 2 moves c3");
+    tei.handle("go depth 2");
+    let lines = until(&receiver, "bestmove");
+    let best = lines.last().unwrap()["bestmove ".len()..].to_string();
+
+    let mut game = Game::from_tps("x3/x,2,x/1,x2 2 2").unwrap();
+    game.play_simple("c3").unwrap();
+    assert!(game.play_simple(&best).is_ok(), "{}", best);
+}
+
+#[test]
+fn stop_ends_search() {
+    let (mut tei, receiver) = engine();
+    tei.handle("position startpos moves a1 e5");
+    tei.handle("go infinite");
+    tei.handle("stop");
+    let lines = until(&receiver, "bestmove");
+    assert!(lines.iter().any(|line| line.starts_with("info depth 1 ")));
+}
+
+#[test]
+fn reports_bad_input() {
+    let (mut tei, receiver) = engine();
+    tei.handle("position startpos moves a1 a1");
+    assert!(receiver.recv().unwrap().starts_with("info string Illegal move a1"));
+    tei.handle("go sometime 3");
+    assert!(receiver.recv().unwrap().starts_with("info string Unknown go argument"));
+    tei.handle("setoption name Size value 9");
+    assert_eq!(receiver.recv().unwrap(), "info string Invalid size: 9");
+}
@@ -0,0 +1,46 @@
+// Run with `wasm-pack test --headless --firefox --features wasm` (or
+// `--node`)
+#![cfg(all(target_arch = "wasm32", feature = "wasm"))]
+
+extern crate tak;
+extern crate wasm_bindgen_test;
+
+use wasm_bindgen_test::*;
+
+use tak::wasm::WasmGame;
+
+#[wasm_bindgen_test]
+fn play_to_a_road() {
+    let mut game = WasmGame::new(3).unwrap();
+    assert_eq!(game.legal_moves().len(), 9);
+    for turn in ["a3", "c3", "a1", "b3", "b1", "a2"].iter() {
+        game.play(turn).unwrap();
+    }
+    assert_eq!(game.winner(), None);
+    assert!(game.is_legal("c1"));
+    assert!(!game.is_legal("a1"));
+    assert!(game.play("a1").is_err());
+    game.play("c1").unwrap();
+    assert_eq!(game.winner(), Some(1));
+    assert_eq!(game.result(), "R-0");
+    assert!(game.legal_moves().is_empty());
+}
+
+#[wasm_bindgen_test]
+fn import_and_export() {
+    let game = WasmGame::from_tps("x3/x,2,x/1,x2 2 2").unwrap();
+    assert_eq!(game.size(), 3);
+    assert_eq!(game.next_player(), 2);
+    assert_eq!(game.turn_number(), 3);
+    assert_eq!(game.to_tps(), "x3/x,2,x/1,x2 2 2");
+
+    let mut game = WasmGame::new(4).unwrap();
+    game.play("a1").unwrap();
+    game.play("d4").unwrap();
+    let copy = WasmGame::from_ptn(&game.to_ptn()).unwrap();
+    assert_eq!(copy.to_tps(), game.to_tps());
+
+    assert!(WasmGame::new(9).is_err());
+    assert!(WasmGame::from_tps("x3/x3 1 1").is_err());
+    assert!(WasmGame::from_ptn("[Size \"4\"]\n1. a1 a1").is_err());
+}